@@ -16,6 +16,7 @@ impl Plugin for AnimationPlugin {
     }
 }
 
+/// Frames are played backward when `first` is greater than `last`
 #[derive(Component, Reflect, Default, Clone)]
 pub struct AnimationIndices {
     pub first: usize,
@@ -35,7 +36,7 @@ impl AnimationState {
     }
 }
 
-#[derive(Component, Reflect, Default, PartialEq)]
+#[derive(Component, Reflect, Default, PartialEq, Clone, Copy)]
 pub enum AnimationFlip {
     #[default]
     False,
@@ -77,7 +78,6 @@ impl AnimationStateMachine {
         new
     }
 
-    #[allow(dead_code)]
     pub fn insert<T: Debug>(
         &mut self,
         key: T,
@@ -136,6 +136,8 @@ pub fn animate_sprites(
                 *current_handle = sprite.clone();
                 current_sprite.index = if current_sprite.index == indices.last {
                     indices.first
+                } else if indices.first > indices.last {
+                    current_sprite.index - 1
                 } else {
                     current_sprite.index + 1
                 };
@@ -66,6 +66,9 @@ fn calculate_camera_size(
     query: Query<(&Transform, With<PlayerStats>)>,
     mut camera: Query<(&mut CameraData, With<Camera2d>)>,
 ) {
+    if query.is_empty() {
+        return;
+    }
     for (mut camera_data, _) in &mut camera {
         let average_player_positions: Vec2 = query
             .iter()
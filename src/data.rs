@@ -0,0 +1,17 @@
+use std::fs;
+
+use bevy::asset::FileAssetIo;
+use serde::de::DeserializeOwned;
+
+/// Reads a RON definition file from the `assets/data` folder
+pub fn load_data<T: DeserializeOwned>(file: &str) -> T {
+    let path = FileAssetIo::get_base_path()
+        .join("assets")
+        .join("data")
+        .join(file);
+    let content = fs::read_to_string(&path)
+        .unwrap_or_else(|err| panic!("Could not read {}: {err}", path.display()));
+
+    ron::from_str(&content)
+        .unwrap_or_else(|err| panic!("Invalid data in {}: {err}", path.display()))
+}
@@ -2,12 +2,14 @@
 
 pub mod animation;
 pub mod camera;
+pub mod data;
 pub mod debug;
 pub mod map;
 pub mod mouse;
 pub mod physics;
 pub mod player;
 pub mod rendering;
+pub mod save;
 pub mod ui;
 
 use leafwing_input_manager::plugin::InputManagerSystem;
@@ -25,6 +27,7 @@ fn main() {
             camera::CameraPlugin,
             player::PlayerPlugin,
             map::MapPlugin,
+            save::SavePlugin,
         ))
         .add_systems(Startup, setup)
         .add_systems(
@@ -0,0 +1,74 @@
+use bevy::{math::Vec3Swizzles, prelude::*};
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::player::{
+    interact::{InteractEvent, Interactable},
+    inventory::loot::LootDrop,
+};
+
+const OPENED_SPRITE: &str =
+    "0x72_16x16DungeonTileset.v5/0x72_16x16DungeonTileset.v5/items/chest_open_empty.png";
+/// Where the loot spills, relative to the chest
+const DROP_OFFSET: Vec2 = Vec2::new(0., -16.);
+
+#[derive(Component, Clone, Default)]
+pub struct Chest {
+    pub table: String,
+    pub rolls: u32,
+    pub opened: bool,
+}
+
+impl From<&EntityInstance> for Chest {
+    fn from(entity: &EntityInstance) -> Self {
+        Chest {
+            table: entity
+                .get_string_field("table")
+                .map_or_else(|_| "chest".to_string(), Clone::clone),
+            rolls: entity
+                .get_int_field("rolls")
+                .map_or(1, |rolls| *rolls as u32),
+            opened: false,
+        }
+    }
+}
+
+#[derive(Bundle, LdtkEntity)]
+pub struct ChestBundle {
+    #[sprite_bundle(
+        "0x72_16x16DungeonTileset.v5/0x72_16x16DungeonTileset.v5/items/chest_closed.png"
+    )]
+    sprite_bundle: SpriteBundle,
+    #[from_entity_instance]
+    chest: Chest,
+    #[with(chest_interactable)]
+    interactable: Interactable,
+}
+
+fn chest_interactable(_: &EntityInstance) -> Interactable {
+    Interactable::new("Open chest")
+}
+
+pub fn open_chests(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut ev_interact: EventReader<InteractEvent>,
+    mut ev_drop: EventWriter<LootDrop>,
+    mut chests: Query<(&mut Chest, &mut Handle<Image>, &GlobalTransform)>,
+) {
+    for interaction in ev_interact.iter() {
+        let Ok((mut chest, mut sprite, transform)) = chests.get_mut(interaction.target) else {
+            continue;
+        };
+        if chest.opened {
+            continue;
+        }
+        chest.opened = true;
+        *sprite = asset_server.load(OPENED_SPRITE);
+        commands.entity(interaction.target).remove::<Interactable>();
+        ev_drop.send(LootDrop {
+            table: chest.table.clone(),
+            rolls: chest.rolls,
+            position: transform.translation().xy() + DROP_OFFSET,
+        });
+    }
+}
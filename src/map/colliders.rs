@@ -3,6 +3,10 @@ use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
 use std::collections::{HashMap, HashSet};
 
+/// IntGrid layer holding the walls, other layers reuse its values for other things
+pub const WALLS_LAYER: &str = "BasicLayer";
+pub const WALL_VALUE: i32 = 1;
+
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
 pub struct Wall;
 
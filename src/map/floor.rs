@@ -0,0 +1,402 @@
+use bevy::{prelude::*, utils::HashMap};
+use bevy_ecs_ldtk::{ldtk::Level, prelude::*};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serde::Deserialize;
+
+use crate::{data::load_data, player::inventory::loot::RunSeed};
+
+use super::{
+    colliders::{WALLS_LAYER, WALL_VALUE},
+    FALLBACK_LEVEL,
+};
+
+/// Width and height of every room template, and of the grid the floor is laid on
+pub const ROOM_SIZE: f32 = 480.;
+/// Floors failing to find templates for every room are rolled again this many times
+const MAX_ATTEMPTS: usize = 100;
+/// Cells along the edge of a template that a door socket opens
+const DOOR_CELLS: [i32; 2] = [15, 16];
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RoomKind {
+    Start,
+    Combat,
+    Treasure,
+    Shop,
+    Boss,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Door {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Door {
+    pub const ALL: [Door; 4] = [Door::North, Door::East, Door::South, Door::West];
+
+    pub fn offset(self) -> IVec2 {
+        match self {
+            Door::North => IVec2::Y,
+            Door::East => IVec2::X,
+            Door::South => IVec2::NEG_Y,
+            Door::West => IVec2::NEG_X,
+        }
+    }
+
+    pub fn opposite(self) -> Door {
+        match self {
+            Door::North => Door::South,
+            Door::East => Door::West,
+            Door::South => Door::North,
+            Door::West => Door::East,
+        }
+    }
+
+    fn from_letter(letter: char) -> Option<Door> {
+        match letter.to_ascii_uppercase() {
+            'N' => Some(Door::North),
+            'E' => Some(Door::East),
+            'S' => Some(Door::South),
+            'W' => Some(Door::West),
+            _ => None,
+        }
+    }
+
+    /// Grid coordinates of the opening, from the top left of a level `size` cells wide
+    fn opening(self, size: i32) -> [IVec2; 2] {
+        DOOR_CELLS.map(|i| match self {
+            Door::North => IVec2::new(i, 0),
+            Door::East => IVec2::new(size - 1, i),
+            Door::South => IVec2::new(i, size - 1),
+            Door::West => IVec2::new(0, i),
+        })
+    }
+
+    /// Whether the walls layer of the level leaves the opening free
+    fn is_open(self, level: &Level) -> bool {
+        level
+            .layer_instances
+            .iter()
+            .flatten()
+            .filter(|layer| layer.identifier == WALLS_LAYER)
+            .all(|layer| {
+                self.opening(layer.c_wid).iter().all(|cell| {
+                    layer
+                        .int_grid_csv
+                        .get((cell.y * layer.c_wid + cell.x) as usize)
+                        != Some(&WALL_VALUE)
+                })
+            })
+    }
+}
+
+/// Where LDtk places a level, relative to the world
+pub fn level_translation(level: &Level) -> Vec2 {
+    Vec2::new(level.world_x as f32, -(level.world_y + level.px_hei) as f32)
+}
+
+/// LDtk level tagged with a `room_kind` field, `doors` lists its sockets as "NESW" letters
+#[derive(Clone, Debug)]
+pub struct RoomTemplate {
+    pub iid: String,
+    pub kind: RoomKind,
+    pub doors: Vec<Door>,
+    /// Where LDtk places the level, the floor is laid around the start room's one
+    pub translation: Vec2,
+}
+
+impl RoomTemplate {
+    fn from_level(level: &Level) -> Option<Self> {
+        let kind = level.get_string_field("room_kind").ok()?;
+        let Ok(kind) = ron::from_str::<RoomKind>(kind) else {
+            warn!(
+                "Level {} has the unknown room kind {kind}",
+                level.identifier
+            );
+            return None;
+        };
+        if level.px_wid as f32 != ROOM_SIZE || level.px_hei as f32 != ROOM_SIZE {
+            warn!(
+                "Room template {} is not {ROOM_SIZE} pixels wide",
+                level.identifier
+            );
+            return None;
+        }
+
+        Some(RoomTemplate {
+            iid: level.iid.clone(),
+            kind,
+            doors: level.get_string_field("doors").map_or(Vec::new(), |doors| {
+                doors
+                    .chars()
+                    .filter_map(Door::from_letter)
+                    .filter(|door| {
+                        let open = door.is_open(level);
+                        if !open {
+                            warn!(
+                                "Room template {} has no opening for its {door:?} door",
+                                level.identifier
+                            );
+                        }
+                        open
+                    })
+                    .collect()
+            }),
+            translation: level_translation(level),
+        })
+    }
+
+    fn fits(&self, room: &Room) -> bool {
+        self.kind == room.kind
+            && self.doors.len() == room.doors.len()
+            && room.doors.iter().all(|door| self.doors.contains(door))
+    }
+}
+
+/// How many rooms of each kind a floor has, besides the start and the boss
+#[derive(Resource, Deserialize, Clone, Debug)]
+pub struct FloorRecipe {
+    pub combat_rooms: usize,
+    pub treasure_rooms: usize,
+    pub shop_rooms: usize,
+}
+
+impl Default for FloorRecipe {
+    fn default() -> Self {
+        load_data("floor.ron")
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Room {
+    pub kind: RoomKind,
+    /// Position on the floor grid, north is up
+    pub cell: IVec2,
+    pub doors: Vec<Door>,
+    /// Iid of the LDtk level used for this room
+    pub template: String,
+}
+
+/// Layout of the current floor, rooms are indexed in the order they were generated
+#[derive(Resource, Debug)]
+pub struct Floor {
+    pub rooms: Vec<Room>,
+    /// Rooms behind each door of a room
+    pub adjacency: Vec<Vec<(Door, usize)>>,
+    /// World translation of the grid cell at the origin
+    pub origin: Vec2,
+}
+
+impl Floor {
+    /// Floor made of a single level, left where LDtk places it
+    pub fn single(level: &Level) -> Floor {
+        Floor {
+            rooms: vec![Room {
+                kind: RoomKind::Start,
+                cell: IVec2::ZERO,
+                doors: Vec::new(),
+                template: level.iid.clone(),
+            }],
+            adjacency: vec![Vec::new()],
+            origin: level_translation(level),
+        }
+    }
+
+    pub fn room_translation(&self, room: usize) -> Vec2 {
+        self.origin + self.rooms[room].cell.as_vec2() * ROOM_SIZE
+    }
+
+    /// Room covering a world position, if any
+    pub fn room_at(&self, position: Vec2) -> Option<usize> {
+        let cell = ((position - self.origin) / ROOM_SIZE).floor().as_ivec2();
+
+        self.rooms.iter().position(|room| room.cell == cell)
+    }
+
+    pub fn room_with_template(&self, iid: &str) -> Option<usize> {
+        self.rooms.iter().position(|room| room.template == iid)
+    }
+
+    pub fn iids(&self) -> impl Iterator<Item = &String> {
+        self.rooms.iter().map(|room| &room.template)
+    }
+
+    fn generate(
+        recipe: &FloorRecipe,
+        templates: &[RoomTemplate],
+        rng: &mut impl Rng,
+    ) -> Option<Floor> {
+        let path_length = recipe.combat_rooms + 2;
+        let mut rooms: Vec<Room> = Vec::new();
+        let mut cells: HashMap<IVec2, usize> = HashMap::new();
+
+        let mut add_room =
+            |rooms: &mut Vec<Room>, kind: RoomKind, from: Option<usize>, rng: &mut _| {
+                let Some(from) = from else {
+                    cells.insert(IVec2::ZERO, rooms.len());
+                    rooms.push(Room {
+                        kind,
+                        cell: IVec2::ZERO,
+                        doors: Vec::new(),
+                        template: String::new(),
+                    });
+                    return true;
+                };
+                let cell = rooms[from].cell;
+                let free: Vec<Door> = Door::ALL
+                    .into_iter()
+                    .filter(|door| !cells.contains_key(&(cell + door.offset())))
+                    .collect();
+                let Some(door) = free.choose(rng).copied() else {
+                    return false;
+                };
+
+                cells.insert(cell + door.offset(), rooms.len());
+                rooms[from].doors.push(door);
+                rooms.push(Room {
+                    kind,
+                    cell: cell + door.offset(),
+                    doors: vec![door.opposite()],
+                    template: String::new(),
+                });
+                true
+            };
+
+        // Main path from the start to the boss, with side rooms branching off its combat rooms
+        for index in 0..path_length {
+            let kind = match index {
+                0 => RoomKind::Start,
+                index if index == path_length - 1 => RoomKind::Boss,
+                _ => RoomKind::Combat,
+            };
+            if !add_room(&mut rooms, kind, index.checked_sub(1), &mut *rng) {
+                return None;
+            }
+        }
+        let side_rooms = std::iter::repeat(RoomKind::Treasure)
+            .take(recipe.treasure_rooms)
+            .chain(std::iter::repeat(RoomKind::Shop).take(recipe.shop_rooms));
+        for kind in side_rooms {
+            // The start room works as a hub when there is no combat room
+            let branches: Vec<usize> = if recipe.combat_rooms > 0 {
+                (1..path_length - 1).collect()
+            } else {
+                vec![0]
+            };
+            let from = *branches.choose(rng)?;
+            if !add_room(&mut rooms, kind, Some(from), &mut *rng) {
+                return None;
+            }
+        }
+
+        // Each template is a single LDtk level, so it can only be used once per floor
+        let mut used: Vec<&str> = Vec::new();
+        for room in &mut rooms {
+            let candidates: Vec<&RoomTemplate> = templates
+                .iter()
+                .filter(|template| template.fits(room) && !used.contains(&template.iid.as_str()))
+                .collect();
+            let template = candidates.choose(rng)?;
+
+            used.push(&template.iid);
+            room.template = template.iid.clone();
+        }
+
+        let adjacency = rooms
+            .iter()
+            .map(|room| {
+                room.doors
+                    .iter()
+                    .map(|door| (*door, cells[&(room.cell + door.offset())]))
+                    .collect()
+            })
+            .collect();
+        let origin = templates
+            .iter()
+            .find(|template| template.iid == rooms[0].template)?
+            .translation;
+
+        Some(Floor {
+            rooms,
+            adjacency,
+            origin,
+        })
+    }
+}
+
+/// Rolls the floor of the run once the LDtk project is loaded, falling back to [`FALLBACK_LEVEL`]
+pub fn generate_floor(
+    mut commands: Commands,
+    seed: Res<RunSeed>,
+    recipe: Res<FloorRecipe>,
+    floor: Option<Res<Floor>>,
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+    mut worlds: Query<(Entity, &Handle<LdtkAsset>, &mut LevelSet)>,
+) {
+    if floor.is_some() {
+        return;
+    }
+    let Ok((world, handle, mut level_set)) = worlds.get_single_mut() else {
+        return;
+    };
+    let Some(ldtk) = ldtk_assets.get(handle) else {
+        return;
+    };
+    let templates: Vec<RoomTemplate> = ldtk
+        .project
+        .levels
+        .iter()
+        .filter_map(RoomTemplate::from_level)
+        .collect();
+    let mut rng = StdRng::seed_from_u64(seed.0);
+
+    let floor = (0..MAX_ATTEMPTS)
+        .find_map(|_| Floor::generate(&recipe, &templates, &mut rng))
+        .unwrap_or_else(|| {
+            warn!("No floor could be built from the room templates, using the fallback level");
+            ldtk.project
+                .levels
+                .iter()
+                .find(|level| level.iid == FALLBACK_LEVEL)
+                .map_or(
+                    Floor {
+                        rooms: Vec::new(),
+                        adjacency: Vec::new(),
+                        origin: Vec2::ZERO,
+                    },
+                    Floor::single,
+                )
+        });
+
+    level_set.iids = floor.iids().cloned().collect();
+    // Levels are spawned again even when the new floor reuses the same ones
+    commands.entity(world).insert(Respawn);
+    commands.insert_resource(floor);
+}
+
+/// Moves the levels spawned for the floor on their grid cell
+pub fn place_rooms(
+    floor: Option<Res<Floor>>,
+    levels: Res<Assets<LdtkLevel>>,
+    mut spawned: Query<(&Handle<LdtkLevel>, &mut Transform), Added<Handle<LdtkLevel>>>,
+) {
+    let Some(floor) = floor else {
+        return;
+    };
+
+    for (handle, mut transform) in &mut spawned {
+        let Some(room) = levels
+            .get(handle)
+            .and_then(|level| floor.room_with_template(&level.level.iid))
+        else {
+            continue;
+        };
+        let translation = floor.room_translation(room);
+
+        transform.translation.x = translation.x;
+        transform.translation.y = translation.y;
+    }
+}
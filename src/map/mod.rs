@@ -1,10 +1,24 @@
+pub mod chest;
 pub mod colliders;
+pub mod floor;
+pub mod shop;
+pub mod spawn;
+pub mod spawner;
+pub mod surface;
 pub mod switch;
+pub mod transition;
 
-use bevy::prelude::*;
+use bevy::{prelude::*, transform::TransformSystem};
 use bevy_ecs_ldtk::prelude::*;
 
-use self::colliders::WallBundle;
+use self::chest::ChestBundle;
+use self::colliders::{WallBundle, WALL_VALUE};
+use self::floor::FloorRecipe;
+use self::shop::ShopBundle;
+use self::spawn::PlayerSpawnBundle;
+use self::spawner::{GunPickupBundle, ItemPickupBundle, SpawnerBundle};
+use self::surface::{SurfaceBundle, SurfaceMap, SURFACES_LAYER};
+use self::transition::{ChangeLevel, ExitBundle};
 
 #[derive(Bundle, LdtkEntity)]
 pub struct Map {
@@ -18,6 +32,13 @@ impl Plugin for MapPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(LdtkPlugin)
             .register_ldtk_entity::<Map>("Map")
+            .register_ldtk_entity::<ShopBundle>("Shop")
+            .register_ldtk_entity::<ChestBundle>("Chest")
+            .register_ldtk_entity::<PlayerSpawnBundle>("PlayerSpawn")
+            .register_ldtk_entity::<ItemPickupBundle>("ItemPickup")
+            .register_ldtk_entity::<GunPickupBundle>("GunPickup")
+            .register_ldtk_entity::<SpawnerBundle>("Spawner")
+            .register_ldtk_entity::<ExitBundle>("Exit")
             .insert_resource(LdtkSettings {
                 level_background: LevelBackground::Nonexistent,
                 ..default()
@@ -28,25 +49,56 @@ impl Plugin for MapPlugin {
                 blue: 18. / 255.,
                 alpha: 1.,
             }))
-            .register_ldtk_int_cell::<WallBundle>(1)
-            .add_systems(Startup, setup_map)
+            .register_ldtk_int_cell::<WallBundle>(WALL_VALUE)
+            .init_resource::<SurfaceMap>()
+            .init_resource::<FloorRecipe>()
+            .add_event::<ChangeLevel>()
+            .add_systems(Startup, (setup_map, transition::spawn_fade_overlay))
             .add_systems(Update, colliders::spawn_wall_collision)
-            .add_systems(Update, switch::switch_levels);
+            .add_systems(
+                Update,
+                (
+                    transition::use_exits,
+                    transition::start_transitions,
+                    transition::run_transitions,
+                    floor::generate_floor,
+                )
+                    .chain(),
+            )
+            .add_systems(Update, floor::place_rooms)
+            .add_systems(Update, chest::open_chests)
+            .add_systems(
+                PostUpdate,
+                (
+                    surface::update_surface_map,
+                    shop::stock_shops,
+                    spawner::update_spawn_points,
+                    spawn::move_players_to_spawns,
+                )
+                    .after(TransformSystem::TransformPropagate),
+            );
+
+        #[cfg(debug_assertions)]
+        app.add_systems(
+            Update,
+            switch::switch_levels.before(transition::start_transitions),
+        );
+
+        for value in 1..=6 {
+            app.register_ldtk_int_cell_for_layer::<SurfaceBundle>(SURFACES_LAYER, value);
+        }
     }
 }
 
-const LEVEL_IIDS: [&str; 2] = [
-    "65e48640-3b70-11ee-a7e1-116d8f689398",
-    "855efd30-3b70-11ee-acdb-0b20980d1e54",
-];
+/// Level used when no floor can be generated from the room templates
+const FALLBACK_LEVEL: &str = "65e48640-3b70-11ee-a7e1-116d8f689398";
 
 fn setup_map(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let level_set = LevelSet::from_iid(LEVEL_IIDS[0]);
-
+    // Filled by `floor::generate_floor` once the project is loaded
     commands.spawn(LdtkWorldBundle {
         ldtk_handle: asset_server.load("map/map.ldtk"),
         transform: Transform::from_translation(Vec3::new(-240., -240., 0.)),
-        level_set,
+        level_set: LevelSet::default(),
         ..default()
     });
 }
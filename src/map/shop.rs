@@ -0,0 +1,143 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    player::{
+        interact::Interactable,
+        inventory::{
+            loot::{LootConditions, LootSpawner, LootTables, RunSeed},
+            pickup::Price,
+        },
+    },
+    rendering::utils::Zindex,
+};
+
+const PEDESTAL_SPACING: f32 = 24.;
+const PRICE_VARIATION: f32 = 0.2;
+
+/// Row of pedestals selling loot rolled from `table`
+#[derive(Component, Clone, Default)]
+pub struct Shop {
+    pub slots: usize,
+    /// Fixed seed for a hand made shop, derived from the run seed otherwise
+    pub seed: Option<u64>,
+    pub table: String,
+    pub iid: String,
+}
+
+impl From<&EntityInstance> for Shop {
+    fn from(entity: &EntityInstance) -> Self {
+        Shop {
+            slots: entity
+                .get_int_field("slots")
+                .map_or(3, |slots| *slots as usize),
+            seed: entity
+                .get_maybe_int_field("seed")
+                .ok()
+                .copied()
+                .flatten()
+                .map(|seed| seed as u64),
+            table: entity
+                .get_string_field("table")
+                .map_or_else(|_| "shop".to_string(), Clone::clone),
+            iid: entity.iid.clone(),
+        }
+    }
+}
+
+#[derive(Bundle, LdtkEntity)]
+pub struct ShopBundle {
+    #[from_entity_instance]
+    shop: Shop,
+}
+
+pub fn stock_shops(
+    seed: Res<RunSeed>,
+    tables: Res<LootTables>,
+    asset_server: Res<AssetServer>,
+    shops: Query<(&Shop, &GlobalTransform), Added<Shop>>,
+    conditions: LootConditions,
+    mut spawner: LootSpawner,
+) {
+    for (shop, transform) in &shops {
+        let Some(table) = tables.get(&shop.table) else {
+            warn!(
+                "Shop {} uses the unknown loot table {}",
+                shop.iid, shop.table
+            );
+            continue;
+        };
+        let mut rng = StdRng::seed_from_u64(shop.seed.unwrap_or_else(|| {
+            let mut hasher = DefaultHasher::new();
+
+            shop.iid.hash(&mut hasher);
+            seed.0 ^ hasher.finish()
+        }));
+        let center = transform.translation().truncate();
+
+        for slot in 0..shop.slots {
+            let offset = (slot as f32 - (shop.slots - 1) as f32 / 2.) * PEDESTAL_SPACING;
+            let pos = center + Vec2::new(offset, 0.);
+            let Some(entry) = table.roll(&mut rng, &spawner.items, |entry| conditions.allow(entry))
+            else {
+                continue;
+            };
+            let amount = (entry.price as f32
+                * rng.gen_range(1. - PRICE_VARIATION..=1. + PRICE_VARIATION))
+            .round() as u32;
+            let Some(pickup) = spawner.spawn(&entry.loot, pos) else {
+                continue;
+            };
+
+            let pedestal = spawner
+                .commands
+                .spawn((
+                    Name::new("Pedestal"),
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: Color::rgb(0.3, 0.3, 0.35),
+                            custom_size: Some(Vec2::new(16., 4.)),
+                            ..default()
+                        },
+                        transform: Transform::from_translation((pos - Vec2::Y * 10.).extend(0.)),
+                        ..default()
+                    },
+                    Zindex(4.),
+                ))
+                .id();
+            let label = spawner
+                .commands
+                .spawn(Text2dBundle {
+                    text: Text::from_section(
+                        amount.to_string(),
+                        TextStyle {
+                            font: asset_server.load("fonts/Extended_font.ttf"),
+                            font_size: 20.0,
+                            color: Color::GOLD,
+                        },
+                    ),
+                    transform: Transform::from_translation((pos - Vec2::Y * 16.).extend(100.))
+                        .with_scale(Vec3::splat(0.25)),
+                    ..default()
+                })
+                .id();
+
+            spawner.commands.entity(pickup).insert((
+                Price { amount, label },
+                Interactable::new(format!("Buy for {amount}c")),
+            ));
+            if let Ok(ground) = spawner.ground.get_single() {
+                spawner
+                    .commands
+                    .entity(ground)
+                    .push_children(&[pedestal, label]);
+            }
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use bevy::{math::Vec3Swizzles, prelude::*};
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::player::{join::PlayerIndex, team::TeamRules};
+
+use super::floor::Floor;
+
+/// Distance between players sharing a spawn when the level lacks spawns
+const CROWDED_SPACING: f32 = 20.;
+
+/// Where players appear, reserved to a player or a team when the fields are set
+#[derive(Component, Clone, Default)]
+pub struct PlayerSpawn {
+    pub player_index: Option<usize>,
+    pub team: Option<usize>,
+}
+
+impl From<&EntityInstance> for PlayerSpawn {
+    fn from(entity: &EntityInstance) -> Self {
+        let field = |name| {
+            entity
+                .get_maybe_int_field(name)
+                .ok()
+                .copied()
+                .flatten()
+                .map(|value| value as usize)
+        };
+
+        PlayerSpawn {
+            player_index: field("player_index"),
+            team: field("team"),
+        }
+    }
+}
+
+#[derive(Bundle, LdtkEntity)]
+pub struct PlayerSpawnBundle {
+    #[from_entity_instance]
+    spawn: PlayerSpawn,
+}
+
+/// Picks the spawn of each player, in order of preference: the one reserved to the player,
+/// one reserved to its team, then any free one. Players without a free spawn share one.
+fn assign_spawns(
+    spawns: &[(&PlayerSpawn, Vec2)],
+    players: &[usize],
+    rules: &TeamRules,
+) -> Vec<Vec2> {
+    let mut taken = vec![false; spawns.len()];
+    let mut positions = Vec::new();
+
+    for (order, index) in players.iter().enumerate() {
+        let team = rules.mode.team_of(*index).0;
+        let preferences: [&dyn Fn(&PlayerSpawn) -> bool; 4] = [
+            &|spawn| spawn.player_index == Some(*index),
+            &|spawn| spawn.player_index.is_none() && spawn.team == Some(team),
+            &|spawn| spawn.player_index.is_none() && spawn.team.is_none(),
+            &|_| true,
+        ];
+        let found = preferences
+            .iter()
+            .find_map(|matches| (0..spawns.len()).find(|&i| !taken[i] && matches(spawns[i].0)));
+
+        match found {
+            Some(i) => {
+                taken[i] = true;
+                positions.push(spawns[i].1);
+            }
+            None => {
+                let shared = spawns[order % spawns.len()].1;
+                positions.push(shared + Vec2::X * CROWDED_SPACING * (order / spawns.len()) as f32);
+            }
+        }
+    }
+    positions
+}
+
+/// Moves every player on a spawn of the start room when a level loads, and new players on join
+pub fn move_players_to_spawns(
+    rules: Res<TeamRules>,
+    floor: Option<Res<Floor>>,
+    worlds: Query<&GlobalTransform, With<Handle<LdtkAsset>>>,
+    spawns: Query<(&PlayerSpawn, &GlobalTransform)>,
+    new_spawns: Query<(), Added<PlayerSpawn>>,
+    mut players: Query<(Ref<PlayerIndex>, &mut Transform)>,
+) {
+    let level_loaded = !new_spawns.is_empty();
+    if !level_loaded && !players.iter().any(|(index, _)| index.is_added()) {
+        return;
+    }
+    let world = worlds
+        .get_single()
+        .map_or(Vec2::ZERO, |transform| transform.translation().xy());
+    // Other rooms of the floor keep their spawns for later
+    let spawns: Vec<(&PlayerSpawn, Vec2)> = spawns
+        .iter()
+        .map(|(spawn, transform)| (spawn, transform.translation().xy()))
+        .filter(|(_, position)| {
+            floor.as_ref().map_or(true, |floor| {
+                floor.rooms.len() <= 1 || floor.room_at(*position - world) == Some(0)
+            })
+        })
+        .collect();
+    if spawns.is_empty() {
+        return;
+    }
+
+    let mut indices: Vec<usize> = players.iter().map(|(index, _)| index.0).collect();
+    indices.sort();
+    let positions = assign_spawns(&spawns, &indices, &rules);
+
+    for (index, mut transform) in &mut players {
+        if !level_loaded && !index.is_added() {
+            continue;
+        }
+        let Some(order) = indices.iter().position(|i| *i == index.0) else {
+            continue;
+        };
+        transform.translation.x = positions[order].x;
+        transform.translation.y = positions[order].y;
+    }
+}
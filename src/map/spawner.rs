@@ -0,0 +1,156 @@
+use bevy::{math::Vec3Swizzles, prelude::*};
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::player::inventory::{
+    loot::{Loot, LootConditions, LootSpawner, LootTables},
+    pickup::Pickup,
+    weapon_manager::Guns,
+};
+
+#[derive(Clone, Debug)]
+pub enum PlacedLoot {
+    Fixed(Loot),
+    /// Name of a loot table rolled on every spawn
+    Table(String),
+}
+
+/// Place where LDtk puts a pickup, spawned again `respawn` seconds after it is taken
+#[derive(Component, Clone, Default)]
+pub struct LootSpawnPoint {
+    /// `None` when the LDtk fields name nothing valid
+    pub loot: Option<PlacedLoot>,
+    /// The pickup only spawns once without it
+    pub respawn: Option<f32>,
+    pub iid: String,
+    /// Pickup currently lying on the spawn point
+    pub pickup: Option<Entity>,
+    /// Seconds left before the next spawn
+    pub cooldown: f32,
+    pub exhausted: bool,
+}
+
+impl LootSpawnPoint {
+    fn new(entity: &EntityInstance, loot: Option<PlacedLoot>) -> Self {
+        LootSpawnPoint {
+            loot,
+            respawn: entity
+                .get_maybe_float_field("respawn")
+                .ok()
+                .copied()
+                .flatten(),
+            iid: entity.iid.clone(),
+            ..default()
+        }
+    }
+}
+
+fn item_spawn_point(entity: &EntityInstance) -> LootSpawnPoint {
+    let item = entity
+        .get_string_field("item")
+        .ok()
+        .map(|name| PlacedLoot::Fixed(Loot::Item(name.clone())));
+
+    LootSpawnPoint::new(entity, item)
+}
+
+fn gun_spawn_point(entity: &EntityInstance) -> LootSpawnPoint {
+    let gun = entity.get_string_field("gun").ok().and_then(|name| {
+        ron::from_str::<Guns>(name)
+            .map_err(|_| warn!("Gun pickup {} names the unknown gun {name}", entity.iid))
+            .ok()
+    });
+
+    LootSpawnPoint::new(entity, gun.map(|gun| PlacedLoot::Fixed(Loot::Gun(gun))))
+}
+
+fn table_spawn_point(entity: &EntityInstance) -> LootSpawnPoint {
+    let table = entity
+        .get_string_field("table")
+        .map_or_else(|_| "spawner".to_string(), Clone::clone);
+
+    LootSpawnPoint::new(entity, Some(PlacedLoot::Table(table)))
+}
+
+#[derive(Bundle, LdtkEntity)]
+pub struct ItemPickupBundle {
+    #[with(item_spawn_point)]
+    spawn_point: LootSpawnPoint,
+}
+
+#[derive(Bundle, LdtkEntity)]
+pub struct GunPickupBundle {
+    #[with(gun_spawn_point)]
+    spawn_point: LootSpawnPoint,
+}
+
+#[derive(Bundle, LdtkEntity)]
+pub struct SpawnerBundle {
+    #[with(table_spawn_point)]
+    spawn_point: LootSpawnPoint,
+}
+
+pub fn update_spawn_points(
+    time: Res<Time>,
+    tables: Res<LootTables>,
+    pickups: Query<(), With<Pickup>>,
+    mut spawn_points: Query<(&mut LootSpawnPoint, &GlobalTransform)>,
+    conditions: LootConditions,
+    mut spawner: LootSpawner,
+) {
+    let mut rng = rand::thread_rng();
+
+    for (mut spawn_point, transform) in &mut spawn_points {
+        if spawn_point.exhausted {
+            continue;
+        }
+        if let Some(pickup) = spawn_point.pickup {
+            // Taken guns stay alive in the armory, without their `Pickup`
+            if pickups.contains(pickup) {
+                continue;
+            }
+            spawn_point.pickup = None;
+            match spawn_point.respawn {
+                Some(respawn) => spawn_point.cooldown = respawn,
+                None => {
+                    spawn_point.exhausted = true;
+                    continue;
+                }
+            }
+        }
+        if spawn_point.cooldown > 0. {
+            spawn_point.cooldown -= time.delta_seconds();
+            continue;
+        }
+
+        let loot = match spawn_point.loot.clone() {
+            Some(PlacedLoot::Fixed(loot)) => Some(loot),
+            Some(PlacedLoot::Table(name)) => {
+                let Some(table) = tables.get(&name) else {
+                    warn!(
+                        "Spawner {} uses the unknown loot table {name}",
+                        spawn_point.iid
+                    );
+                    spawn_point.exhausted = true;
+                    continue;
+                };
+                table
+                    .roll(&mut rng, &spawner.items, |entry| conditions.allow(entry))
+                    .map(|entry| entry.loot.clone())
+            }
+            None => None,
+        };
+        let Some(loot) = loot else {
+            spawn_point.exhausted = spawn_point.loot.is_none();
+            continue;
+        };
+
+        spawn_point.pickup = spawner.spawn(&loot, transform.translation().xy());
+        if spawn_point.pickup.is_none() {
+            warn!(
+                "Spawn point {} places the unknown {loot:?}",
+                spawn_point.iid
+            );
+            spawn_point.exhausted = true;
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+pub const SURFACES_LAYER: &str = "Surfaces";
+
+const TILE_SIZE: f32 = 16.;
+const CONVEYOR_SPEED: f32 = 35.;
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, Component)]
+pub enum Surface {
+    #[default]
+    Normal,
+    Ice,
+    Mud,
+    /// Pushes players along the given direction
+    Conveyor(Vec2),
+}
+
+impl From<IntGridCell> for Surface {
+    fn from(cell: IntGridCell) -> Self {
+        match cell.value {
+            1 => Surface::Ice,
+            2 => Surface::Mud,
+            3 => Surface::Conveyor(Vec2::Y),
+            4 => Surface::Conveyor(Vec2::NEG_Y),
+            5 => Surface::Conveyor(Vec2::NEG_X),
+            6 => Surface::Conveyor(Vec2::X),
+            _ => Surface::Normal,
+        }
+    }
+}
+
+impl Surface {
+    /// Multiplier applied to the top speed of the players walking on it
+    pub fn speed(&self) -> f32 {
+        match self {
+            Surface::Mud => 0.5,
+            _ => 1.,
+        }
+    }
+
+    /// Multiplier applied to the acceleration and deceleration of the players
+    pub fn traction(&self) -> f32 {
+        match self {
+            Surface::Ice => 0.12,
+            _ => 1.,
+        }
+    }
+
+    /// Velocity added to the players standing on it
+    pub fn push(&self) -> Vec2 {
+        match self {
+            Surface::Conveyor(direction) => *direction * CONVEYOR_SPEED,
+            _ => Vec2::ZERO,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Bundle, LdtkIntCell)]
+pub struct SurfaceBundle {
+    #[from_int_grid_cell]
+    surface: Surface,
+}
+
+/// Surfaces of the loaded levels, indexed by world tile coordinates
+#[derive(Resource, Default)]
+pub struct SurfaceMap(HashMap<IVec2, Surface>);
+
+impl SurfaceMap {
+    fn tile(position: Vec2) -> IVec2 {
+        (position / TILE_SIZE).floor().as_ivec2()
+    }
+
+    pub fn at(&self, position: Vec2) -> Surface {
+        self.0
+            .get(&SurfaceMap::tile(position))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
+pub fn update_surface_map(
+    mut map: ResMut<SurfaceMap>,
+    surfaces: Query<(&Surface, &GlobalTransform)>,
+    moved: Query<(), (With<Surface>, Changed<GlobalTransform>)>,
+    mut removed: RemovedComponents<Surface>,
+) {
+    let removed = removed.iter().count() > 0;
+
+    if moved.is_empty() && !removed {
+        return;
+    }
+    map.0 = surfaces
+        .iter()
+        .map(|(surface, transform)| {
+            (
+                SurfaceMap::tile(transform.translation().truncate()),
+                *surface,
+            )
+        })
+        .collect();
+}
@@ -0,0 +1,195 @@
+use bevy::{math::Vec3Swizzles, prelude::*};
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::player::{
+    bullets::BulletStats,
+    inventory::{loot::RunSeed, pickup::Ground},
+    join::PlayerIndex,
+};
+
+use super::floor::Floor;
+
+/// Seconds to fade to black, and as much to fade back in
+const FADE_DURATION: f32 = 0.4;
+
+/// Zone taking the players to `target` once all of them stand in it
+#[derive(Component, Clone, Default)]
+pub struct Exit {
+    /// Identifier of the LDtk level to load, a new floor when `None`
+    pub target: Option<String>,
+    pub size: Vec2,
+}
+
+impl From<&EntityInstance> for Exit {
+    fn from(entity: &EntityInstance) -> Self {
+        Exit {
+            target: entity
+                .get_maybe_string_field("target")
+                .ok()
+                .cloned()
+                .flatten(),
+            size: Vec2::new(entity.width as f32, entity.height as f32),
+        }
+    }
+}
+
+#[derive(Bundle, LdtkEntity)]
+pub struct ExitBundle {
+    #[sprite_bundle(
+        "0x72_16x16DungeonTileset.v5/0x72_16x16DungeonTileset.v5/items/Floor_ladder.png"
+    )]
+    sprite_bundle: SpriteBundle,
+    #[from_entity_instance]
+    exit: Exit,
+}
+
+/// Leaves the current level, for the level named `target` or a new floor when `None`
+#[derive(Event, Clone, Debug)]
+pub struct ChangeLevel {
+    pub target: Option<String>,
+}
+
+/// Level change in progress, the level is swapped once the screen is black
+#[derive(Resource)]
+pub struct Transition {
+    pub target: Option<String>,
+    pub elapsed: f32,
+    pub switched: bool,
+}
+
+#[derive(Component)]
+pub struct FadeOverlay;
+
+pub fn spawn_fade_overlay(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                ..default()
+            },
+            background_color: Color::BLACK.with_a(0.).into(),
+            z_index: ZIndex::Global(i32::MAX),
+            ..default()
+        },
+        FadeOverlay,
+        Name::new("Fade overlay"),
+    ));
+}
+
+pub fn use_exits(
+    transition: Option<Res<Transition>>,
+    exits: Query<(&Exit, &GlobalTransform)>,
+    players: Query<&GlobalTransform, With<PlayerIndex>>,
+    mut ev_change: EventWriter<ChangeLevel>,
+) {
+    if transition.is_some() || players.is_empty() {
+        return;
+    }
+
+    for (exit, transform) in &exits {
+        let center = transform.translation().xy();
+        let everyone_in = players.iter().all(|player| {
+            let offset = (player.translation().xy() - center).abs();
+
+            offset.x <= exit.size.x / 2. && offset.y <= exit.size.y / 2.
+        });
+
+        if everyone_in {
+            ev_change.send(ChangeLevel {
+                target: exit.target.clone(),
+            });
+            return;
+        }
+    }
+}
+
+pub fn start_transitions(
+    mut commands: Commands,
+    transition: Option<Res<Transition>>,
+    mut ev_change: EventReader<ChangeLevel>,
+) {
+    let Some(change) = ev_change.iter().last() else {
+        return;
+    };
+    if transition.is_none() {
+        commands.insert_resource(Transition {
+            target: change.target.clone(),
+            elapsed: 0.,
+            switched: false,
+        });
+    }
+}
+
+/// Fades out, swaps the level while the screen is black, then fades back in.
+/// Players are kept with their loadout, bullets and everything lying on the ground are removed.
+pub fn run_transitions(
+    mut commands: Commands,
+    time: Res<Time>,
+    transition: Option<ResMut<Transition>>,
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+    mut overlays: Query<&mut BackgroundColor, With<FadeOverlay>>,
+    mut worlds: Query<(Entity, &Handle<LdtkAsset>, &mut LevelSet)>,
+    ground: Query<Entity, With<Ground>>,
+    bullets: Query<Entity, With<BulletStats>>,
+) {
+    let Some(mut transition) = transition else {
+        return;
+    };
+    transition.elapsed += time.delta_seconds();
+
+    if !transition.switched && transition.elapsed >= FADE_DURATION {
+        transition.switched = true;
+
+        for entity in &ground {
+            commands.entity(entity).despawn_descendants();
+        }
+        for entity in &bullets {
+            commands.entity(entity).despawn_recursive();
+        }
+        let level = transition.target.as_ref().and_then(|target| {
+            let found = worlds
+                .get_single()
+                .ok()
+                .and_then(|(_, handle, _)| ldtk_assets.get(handle))
+                .and_then(|ldtk| {
+                    ldtk.project
+                        .levels
+                        .iter()
+                        .find(|level| level.identifier == *target)
+                });
+
+            if found.is_none() {
+                warn!("Exit to the unknown level {target}, generating a new floor");
+            }
+            found
+        });
+
+        match (level, worlds.get_single_mut()) {
+            (Some(level), Ok((world, _, mut level_set))) => {
+                level_set.iids = [level.iid.clone()].into_iter().collect();
+                commands.entity(world).insert(Respawn);
+                commands.insert_resource(Floor::single(level));
+            }
+            // `generate_floor` rolls the next one
+            _ => {
+                commands.insert_resource(RunSeed::default());
+                commands.remove_resource::<Floor>();
+            }
+        }
+    }
+
+    let progress = (transition.elapsed / FADE_DURATION).min(2.);
+    let alpha = if progress <= 1. {
+        progress
+    } else {
+        2. - progress
+    };
+    for mut color in &mut overlays {
+        color.0 = Color::BLACK.with_a(alpha);
+    }
+    if progress >= 2. {
+        commands.remove_resource::<Transition>();
+    }
+}
@@ -0,0 +1,322 @@
+use std::time::Duration;
+
+use bevy::{math::Vec3Swizzles, prelude::*, sprite::MaterialMesh2dBundle};
+use bevy_rapier2d::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+use serde::Deserialize;
+
+use crate::{
+    data::load_data,
+    map::colliders::WallCollider,
+    rendering::{outline::Outline, utils::Zindex},
+};
+
+use super::{
+    bullets::BulletStats,
+    class::ChoosingClass,
+    direction::{CursorPosition, MoveDirection},
+    downed::Downed,
+    input::PlayerActions,
+    inventory::pickup::{PickupBundle, PickupType},
+    team::Team,
+    weapon::{GunEntity, GunStats},
+};
+
+const BLINK_MARGIN: f32 = 6.;
+const BUBBLE_OPACITY: f32 = 0.25;
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub enum AbilityEffect {
+    /// Teleports the player along its movement, stopping before walls
+    Blink { distance: f32 },
+    /// Wall blocking bullets, placed in front of the player
+    Barrier {
+        width: f32,
+        distance: f32,
+        duration: f32,
+    },
+    /// Refills the magazines of the player and its teammates around
+    AmmoPulse { radius: f32, magazines: i32 },
+    /// Slows down the bullets of other teams inside of it
+    TimeBubble {
+        radius: f32,
+        slow: f32,
+        duration: f32,
+    },
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct AbilityDefinition {
+    pub name: String,
+    pub cooldown: f32,
+    pub icon: String,
+    pub effect: AbilityEffect,
+}
+
+pub struct Ability {
+    pub definition: AbilityDefinition,
+    pub icon: Handle<Image>,
+}
+
+#[derive(Resource)]
+pub struct Abilities(pub Vec<Ability>);
+
+impl FromWorld for Abilities {
+    fn from_world(world: &mut World) -> Self {
+        let definitions: Vec<AbilityDefinition> = load_data("abilities.ron");
+        let asset_server = world.resource::<AssetServer>();
+
+        Abilities(
+            definitions
+                .into_iter()
+                .map(|definition| Ability {
+                    icon: asset_server.load(definition.icon.as_str()),
+                    definition,
+                })
+                .collect(),
+        )
+    }
+}
+
+impl Abilities {
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.0
+            .iter()
+            .position(|ability| ability.definition.name == name)
+    }
+
+    pub fn to_pickup(
+        &self,
+        index: usize,
+        pos: Vec2,
+        meshes: &mut ResMut<Assets<Mesh>>,
+        materials: &mut ResMut<Assets<Outline>>,
+    ) -> PickupBundle {
+        PickupBundle::create(
+            meshes,
+            materials,
+            self.0[index].icon.clone(),
+            Vec2::new(16., 16.),
+            self.0[index].definition.name.clone(),
+            pos,
+            PickupType::Ability(index),
+        )
+    }
+}
+
+/// Equipped ability, a player holds at most one
+#[derive(Component)]
+pub struct AbilitySlot {
+    pub ability: usize,
+    pub cooldown: Timer,
+}
+
+impl AbilitySlot {
+    /// The ability is ready as soon as it is equipped
+    pub fn new(ability: usize, abilities: &Abilities) -> Self {
+        let duration = Duration::from_secs_f32(abilities.0[ability].definition.cooldown);
+        let mut cooldown = Timer::new(duration, TimerMode::Once);
+
+        cooldown.set_elapsed(duration);
+        AbilitySlot { ability, cooldown }
+    }
+}
+
+#[derive(Component)]
+pub struct Barrier {
+    pub lifetime: Timer,
+}
+
+#[derive(Component)]
+pub struct TimeBubble {
+    pub radius: f32,
+    pub slow: f32,
+    pub team: Option<Team>,
+    pub lifetime: Timer,
+}
+
+#[derive(Event)]
+pub struct AmmoPulseEvent {
+    pub source: Entity,
+    pub position: Vec2,
+    pub radius: f32,
+    pub magazines: i32,
+}
+
+type AbilityUser<'a> = (
+    Entity,
+    &'a ActionState<PlayerActions>,
+    &'a mut AbilitySlot,
+    &'a mut Transform,
+    &'a MoveDirection,
+    &'a CursorPosition,
+    Option<&'a Team>,
+);
+
+pub fn use_abilities(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    time: Res<Time>,
+    abilities: Res<Abilities>,
+    rapier: Res<RapierContext>,
+    mut ev_pulse: EventWriter<AmmoPulseEvent>,
+    mut players: Query<AbilityUser, (Without<ChoosingClass>, Without<Downed>)>,
+) {
+    for (entity, actions, mut slot, mut transform, direction, cursor, team) in &mut players {
+        slot.cooldown.tick(time.delta());
+        if !slot.cooldown.finished() || !actions.just_pressed(PlayerActions::Ability) {
+            continue;
+        }
+        slot.cooldown.reset();
+
+        let position = transform.translation.xy();
+        let aim = cursor.relative.try_normalize().unwrap_or(Vec2::NEG_Y);
+
+        match abilities.0[slot.ability].definition.effect {
+            AbilityEffect::Blink { distance } => {
+                let heading = direction.value.try_normalize().unwrap_or(aim);
+                let reach = rapier
+                    .cast_ray(
+                        position,
+                        heading,
+                        distance,
+                        true,
+                        QueryFilter::only_fixed().exclude_sensors(),
+                    )
+                    .map_or(distance, |(_, toi)| (toi - BLINK_MARGIN).max(0.));
+
+                transform.translation += (heading * reach).extend(0.);
+            }
+            AbilityEffect::Barrier {
+                width,
+                distance,
+                duration,
+            } => {
+                commands.spawn((
+                    Name::new("Barrier"),
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: team.map_or(Color::WHITE, Team::color).with_a(0.6),
+                            custom_size: Some(Vec2::new(width, 4.)),
+                            ..default()
+                        },
+                        transform: Transform::from_translation(
+                            (position + aim * distance).extend(0.),
+                        )
+                        .with_rotation(Quat::from_rotation_z(aim.perp().y.atan2(aim.perp().x))),
+                        ..default()
+                    },
+                    Zindex(40.),
+                    Collider::cuboid(width / 2., 2.),
+                    RigidBody::Fixed,
+                    ActiveEvents::COLLISION_EVENTS,
+                    WallCollider,
+                    Barrier {
+                        lifetime: Timer::from_seconds(duration, TimerMode::Once),
+                    },
+                ));
+            }
+            AbilityEffect::AmmoPulse { radius, magazines } => {
+                ev_pulse.send(AmmoPulseEvent {
+                    source: entity,
+                    position,
+                    radius,
+                    magazines,
+                });
+            }
+            AbilityEffect::TimeBubble {
+                radius,
+                slow,
+                duration,
+            } => {
+                commands.spawn((
+                    Name::new("Time bubble"),
+                    MaterialMesh2dBundle {
+                        mesh: meshes.add(shape::Circle::new(radius).into()).into(),
+                        material: materials.add(ColorMaterial::from(
+                            Color::rgb(0.6, 0.4, 1.).with_a(BUBBLE_OPACITY),
+                        )),
+                        transform: Transform::from_translation(position.extend(0.)),
+                        ..default()
+                    },
+                    Zindex(60.),
+                    TimeBubble {
+                        radius,
+                        slow,
+                        team: team.copied(),
+                        lifetime: Timer::from_seconds(duration, TimerMode::Once),
+                    },
+                ));
+            }
+        }
+    }
+}
+
+pub fn apply_ammo_pulses(
+    mut ev_pulse: EventReader<AmmoPulseEvent>,
+    players: Query<(Entity, &Transform, Option<&Team>, Option<&GunEntity>)>,
+    mut guns: Query<&mut GunStats>,
+) {
+    for pulse in ev_pulse.iter() {
+        let team = players
+            .get(pulse.source)
+            .ok()
+            .and_then(|(_, _, team, _)| team);
+
+        for (entity, transform, ally_team, gun) in &players {
+            let in_range = transform.translation.xy().distance(pulse.position) <= pulse.radius;
+
+            if !in_range || (entity != pulse.source && (team.is_none() || ally_team != team)) {
+                continue;
+            }
+            if let Some(mut stats) = gun.and_then(|gun| guns.get_mut(gun.0).ok()) {
+                stats.mag_ammo = stats.mag_size;
+                if !stats.infinite {
+                    stats.ammo =
+                        (stats.ammo + stats.mag_size * pulse.magazines).min(stats.max_ammo);
+                }
+            }
+        }
+    }
+}
+
+pub fn update_barriers(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut barriers: Query<(Entity, &mut Barrier)>,
+) {
+    for (entity, mut barrier) in &mut barriers {
+        if barrier.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+pub fn update_time_bubbles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut bubbles: Query<(Entity, &Transform, &mut TimeBubble)>,
+    mut bullets: Query<(&Transform, &mut BulletStats, &mut Velocity)>,
+    teams: Query<&Team>,
+) {
+    for (entity, transform, mut bubble) in &mut bubbles {
+        if bubble.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let center = transform.translation.xy();
+
+        for (bullet_transform, mut stats, mut velocity) in &mut bullets {
+            let allied =
+                bubble.team.is_some() && teams.get(stats.owner).ok() == bubble.team.as_ref();
+
+            if allied || bullet_transform.translation.xy().distance(center) > bubble.radius {
+                continue;
+            }
+            velocity.linvel *= bubble.slow;
+            // Slowed bullets keep their range
+            stats.distance_traveled -= stats.speed * (1. - bubble.slow) * time.delta_seconds();
+        }
+    }
+}
@@ -1,116 +1,84 @@
 use bevy::prelude::*;
-use bevy_asset_loader::prelude::*;
+use serde::Deserialize;
 
-#[derive(AssetCollection, Resource)]
+/// A single row animation inside a sprite sheet
+#[derive(Deserialize, Clone, Debug)]
+pub struct SheetDefinition {
+    pub path: String,
+    pub tile_size: (f32, f32),
+    pub columns: usize,
+    pub padding: (f32, f32),
+    pub offset: (f32, f32),
+}
+
+impl SheetDefinition {
+    pub fn load(
+        &self,
+        asset_server: &AssetServer,
+        atlases: &mut Assets<TextureAtlas>,
+    ) -> Handle<TextureAtlas> {
+        atlases.add(TextureAtlas::from_grid(
+            asset_server.load(self.path.as_str()),
+            Vec2::new(self.tile_size.0, self.tile_size.1),
+            self.columns,
+            1,
+            Some(Vec2::new(self.padding.0, self.padding.1)),
+            Some(Vec2::new(self.offset.0, self.offset.1)),
+        ))
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct SpriteSetDefinition {
+    pub idle: SheetDefinition,
+    pub front: SheetDefinition,
+    pub side_front: SheetDefinition,
+    pub side_back: SheetDefinition,
+    pub back: SheetDefinition,
+    pub dodge_front: SheetDefinition,
+    pub dodge_side_front: SheetDefinition,
+    pub dodge_side_back: SheetDefinition,
+    pub dodge_back: SheetDefinition,
+    /// Color multiplied with every sheet, telling apart classes sharing sheets
+    #[serde(default = "no_tint")]
+    pub tint: (f32, f32, f32),
+}
+
+fn no_tint() -> (f32, f32, f32) {
+    (1., 1., 1.)
+}
+
+#[derive(Clone)]
 pub struct PlayerAssets {
-    #[asset(texture_atlas(
-        tile_size_x = 17.,
-        tile_size_y = 25.,
-        columns = 4,
-        rows = 1,
-        padding_x = 2.,
-        padding_y = 2.,
-        offset_x = 15.,
-        offset_y = 15.
-    ))]
-    #[asset(path = "idle.png")]
     pub idle: Handle<TextureAtlas>,
-    #[asset(texture_atlas(
-        tile_size_x = 17.,
-        tile_size_y = 25.,
-        columns = 6,
-        rows = 1,
-        padding_x = 2.,
-        padding_y = 2.,
-        offset_x = 15.,
-        offset_y = 15.
-    ))]
-    #[asset(path = "run.png")]
     pub front: Handle<TextureAtlas>,
-    #[asset(texture_atlas(
-        tile_size_x = 17.,
-        tile_size_y = 25.,
-        columns = 6,
-        rows = 1,
-        padding_x = 2.,
-        padding_y = 2.,
-        offset_x = 15.,
-        offset_y = 42.
-    ))]
-    #[asset(path = "run.png")]
     pub side_front: Handle<TextureAtlas>,
-    #[asset(texture_atlas(
-        tile_size_x = 17.,
-        tile_size_y = 25.,
-        columns = 6,
-        rows = 1,
-        padding_x = 2.,
-        padding_y = 2.,
-        offset_x = 15.,
-        offset_y = 96.
-    ))]
-    #[asset(path = "run.png")]
     pub side_back: Handle<TextureAtlas>,
-    #[asset(texture_atlas(
-        tile_size_x = 17.,
-        tile_size_y = 25.,
-        columns = 6,
-        rows = 1,
-        padding_x = 2.,
-        padding_y = 2.,
-        offset_x = 15.,
-        offset_y = 69.
-    ))]
-    #[asset(path = "run.png")]
     pub back: Handle<TextureAtlas>,
-    #[asset(texture_atlas(
-        tile_size_x = 21.,
-        tile_size_y = 25.,
-        columns = 9,
-        rows = 1,
-        padding_x = 2.,
-        padding_y = 2.,
-        offset_x = 15.,
-        offset_y = 15.
-    ))]
-    #[asset(path = "dodge.png")]
     pub dodge_front: Handle<TextureAtlas>,
-    #[asset(texture_atlas(
-        tile_size_x = 21.,
-        tile_size_y = 25.,
-        columns = 9,
-        rows = 1,
-        padding_x = 2.,
-        padding_y = 2.,
-        offset_x = 15.,
-        offset_y = 42.
-    ))]
-    #[asset(path = "dodge.png")]
     pub dodge_side_front: Handle<TextureAtlas>,
-    #[asset(texture_atlas(
-        tile_size_x = 21.,
-        tile_size_y = 25.,
-        columns = 9,
-        rows = 1,
-        padding_x = 2.,
-        padding_y = 2.,
-        offset_x = 15.,
-        offset_y = 96.
-    ))]
-    #[asset(path = "dodge.png")]
     pub dodge_side_back: Handle<TextureAtlas>,
-    #[asset(texture_atlas(
-        tile_size_x = 21.,
-        tile_size_y = 25.,
-        columns = 9,
-        rows = 1,
-        padding_x = 2.,
-        padding_y = 2.,
-        offset_x = 15.,
-        offset_y = 69.
-    ))]
-    #[asset(path = "dodge.png")]
     pub dodge_back: Handle<TextureAtlas>,
-    #[asset(path = "collider.png")]
-    pub collider: Handle<Image>,
+    pub tint: Color,
+}
+
+impl PlayerAssets {
+    pub fn load(
+        sprites: &SpriteSetDefinition,
+        asset_server: &AssetServer,
+        atlases: &mut Assets<TextureAtlas>,
+    ) -> Self {
+        PlayerAssets {
+            idle: sprites.idle.load(asset_server, atlases),
+            front: sprites.front.load(asset_server, atlases),
+            side_front: sprites.side_front.load(asset_server, atlases),
+            side_back: sprites.side_back.load(asset_server, atlases),
+            back: sprites.back.load(asset_server, atlases),
+            dodge_front: sprites.dodge_front.load(asset_server, atlases),
+            dodge_side_front: sprites.dodge_side_front.load(asset_server, atlases),
+            dodge_side_back: sprites.dodge_side_back.load(asset_server, atlases),
+            dodge_back: sprites.dodge_back.load(asset_server, atlases),
+            tint: Color::rgb(sprites.tint.0, sprites.tint.1, sprites.tint.2),
+        }
+    }
 }
@@ -1,18 +1,22 @@
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
+use rand::Rng;
 use strum_macros::EnumIter;
 
 use crate::map::colliders::WallCollider;
 use crate::physics::collision_get;
 use crate::rendering::utils::Zindex;
 
-use crate::player::inventory::item_manager::Items;
-
 use super::{
-    inventory::{inventory_manager::Inventory, weapon_manager::GunAssets},
+    damage::{can_hit, DamageEvent, DamageType},
+    inventory::{
+        inventory_manager::Inventory,
+        weapon_manager::{GunAssets, Guns},
+    },
     roll::RollStats,
     setup::PlayerCollider,
     stats::PlayerStats,
+    team::{Team, TeamRules},
     weapon::GunStats,
 };
 
@@ -23,8 +27,14 @@ pub struct BulletStats {
     pub distance: f32,
     pub distance_traveled: f32,
     pub speed: f32,
-    pub mercury_amount: usize,
+    /// Speed gained every second
+    pub acceleration: f32,
     pub owner: Entity,
+    pub weapon: Option<Guns>,
+    pub damage_type: DamageType,
+    pub crit_chance: f32,
+    /// Players the bullet can still go through
+    pub piercing: u32,
 }
 
 #[derive(Bundle)]
@@ -80,7 +90,7 @@ impl BulletBundle {
         barrel_end: Vec2,
         angle: f32,
         inventory: &Inventory,
-        _gun_stats: &GunStats,
+        gun_stats: &GunStats,
         player_stats: &PlayerStats,
         player: Entity,
         spd: f32,
@@ -93,13 +103,18 @@ impl BulletBundle {
                 zindex: Zindex(45.),
                 stats: BulletStats {
                     owner: player,
+                    weapon: gun_stats.gun,
+                    damage_type: bullet_type.into(),
+                    crit_chance: gun_stats.crit_chance + inventory.bullet.crit_chance,
                     distance_traveled: 0.,
                     angle,
-                    distance: dist,
-                    speed: spd / (inventory.amount(Items::Mercury) as f32 * 3. + 1.),
-                    mercury_amount: inventory.amount(Items::Mercury),
+                    distance: dist * inventory.bullet.range,
+                    speed: spd / (inventory.bullet.slowdown + 1.),
+                    acceleration: inventory.bullet.acceleration,
+                    piercing: inventory.bullet.piercing,
                     damages: (damage + player_stats.damages_added)
-                        * player_stats.damages_multiplier,
+                        * player_stats.damages_multiplier
+                        * inventory.bullet.damage,
                 },
                 sprite: SpriteBundle {
                     texture: assets.marine_bullet.clone(),
@@ -113,13 +128,18 @@ impl BulletBundle {
                 zindex: Zindex(45.),
                 stats: BulletStats {
                     owner: player,
+                    weapon: gun_stats.gun,
+                    damage_type: bullet_type.into(),
+                    crit_chance: gun_stats.crit_chance + inventory.bullet.crit_chance,
                     distance_traveled: 0.,
                     angle,
-                    distance: dist,
-                    speed: spd / (inventory.amount(Items::Mercury) as f32 * 3. + 1.),
-                    mercury_amount: inventory.amount(Items::Mercury),
+                    distance: dist * inventory.bullet.range,
+                    speed: spd / (inventory.bullet.slowdown + 1.),
+                    acceleration: inventory.bullet.acceleration,
+                    piercing: inventory.bullet.piercing,
                     damages: (damage + player_stats.damages_added)
-                        * player_stats.damages_multiplier,
+                        * player_stats.damages_multiplier
+                        * inventory.bullet.damage,
                 },
                 sprite: SpriteBundle {
                     texture: assets.flame.clone(),
@@ -135,21 +155,46 @@ impl BulletBundle {
 pub fn detect_collision_bullets(
     mut commands: Commands,
     mut collision_events: EventReader<CollisionEvent>,
-    mut bullets: Query<(Entity, &mut BulletStats)>,
-    mut players: Query<(Entity, &mut PlayerStats, Without<RollStats>)>,
+    mut ev_damage: EventWriter<DamageEvent>,
+    mut bullets: Query<(Entity, &mut BulletStats, &Transform)>,
+    players: Query<(Entity, &PlayerStats, Option<&RollStats>)>,
     mut player_collider: Query<(&Parent, With<PlayerCollider>)>,
     mut walls: Query<With<WallCollider>>,
+    teams: Query<&Team>,
+    rules: Res<TeamRules>,
 ) {
     for collision_event in collision_events.iter() {
         if let CollisionEvent::Started(entity1, entity2, _) = collision_event {
-            if let Some((bullet_id, bullet_stats)) = collision_get!(bullets, entity1, entity2) {
+            if let Some((bullet_id, mut bullet_stats, transform)) =
+                collision_get!(bullets, entity1, entity2)
+            {
                 if collision_get!(walls, entity1, entity2).is_some() {
                     commands.entity(bullet_id).despawn();
                 } else if let Some((player, _)) = collision_get!(player_collider, entity1, entity2) {
-                    if let Ok((id, mut stats, _)) = players.get_mut(player.get()) {
-                        if bullet_stats.owner != id {
-                            commands.entity(bullet_id).despawn();
-                            stats.current_health -= bullet_stats.damages;
+                    if let Ok((id, stats, roll)) = players.get(player.get()) {
+                        let hit = can_hit(
+                            &rules,
+                            teams.get(bullet_stats.owner).ok(),
+                            teams.get(id).ok(),
+                            stats,
+                            roll,
+                        );
+                        if bullet_stats.owner != id && hit {
+                            if bullet_stats.piercing > 0 {
+                                bullet_stats.piercing -= 1;
+                            } else {
+                                commands.entity(bullet_id).despawn();
+                            }
+                            ev_damage.send(DamageEvent {
+                                target: id,
+                                source: Some(bullet_stats.owner),
+                                weapon: bullet_stats.weapon,
+                                damage_type: bullet_stats.damage_type,
+                                amount: bullet_stats.damages,
+                                position: transform.translation.truncate(),
+                                crit: rand::thread_rng()
+                                    .gen_bool(bullet_stats.crit_chance.clamp(0., 1.) as f64),
+                            });
                         }
                     }
                 }
@@ -164,9 +209,7 @@ pub fn move_bullets(
     mut query: Query<(Entity, &mut BulletStats, &mut Velocity)>,
 ) {
     for (entity, mut stats, mut vel) in &mut query {
-        for _ in 0..stats.mercury_amount {
-            stats.speed += time.delta_seconds() * 70.;
-        }
+        stats.speed += time.delta_seconds() * stats.acceleration;
         vel.linvel = Vec2::from_angle(stats.angle) * stats.speed;
         stats.distance_traveled += stats.speed * time.delta_seconds();
         if stats.distance_traveled > stats.distance {
@@ -0,0 +1,301 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::ActionState;
+use serde::Deserialize;
+
+use crate::{
+    animation::{AnimationState, AnimationStateMachine},
+    data::load_data,
+    rendering::outline::Outline,
+};
+
+use super::{
+    ability::{Abilities, AbilitySlot},
+    assets::{PlayerAssets, SpriteSetDefinition},
+    damage::PlayerHealed,
+    input::{PlayerActions, PlayerState},
+    inventory::{
+        inventory_manager::Inventory,
+        item_manager::Items,
+        weapon_manager::{GunAssets, Guns},
+        PickupItemEvent, PickupWeaponEvent,
+    },
+    modifiers::{BaseStats, ModifierKind, ModifierSource, StatModifiers},
+    setup::player_animations,
+    stats::{PlayerStats, Stat},
+    weapon::{GunEntity, GunStats},
+};
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Passive {
+    /// Health regenerated every second
+    Regeneration(f32),
+    /// Speed multiplier applied while health is below a fraction of the maximum
+    Adrenaline { threshold: f32, speed: f32 },
+    /// Reserve ammo given to the held gun every `interval` seconds
+    Resupply { interval: f32 },
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct ClassDefinition {
+    pub name: String,
+    pub stats: Vec<(Stat, f32)>,
+    pub weapon: Option<Guns>,
+    /// Names of the items given when picking the class
+    #[serde(default)]
+    pub items: Vec<String>,
+    #[serde(default)]
+    pub ability: Option<String>,
+    pub passive: Passive,
+    pub sprites: SpriteSetDefinition,
+}
+
+impl ClassDefinition {
+    pub fn base_stats(&self) -> PlayerStats {
+        let mut stats = PlayerStats::default();
+
+        for (stat, value) in &self.stats {
+            *stats.stat_mut(*stat) = *value;
+        }
+        stats.current_health = stats.max_health;
+        stats.current_stamina = stats.max_stamina;
+        stats
+    }
+}
+
+pub struct CharacterClass {
+    pub definition: ClassDefinition,
+    pub assets: PlayerAssets,
+}
+
+#[derive(Resource)]
+pub struct CharacterClasses(pub Vec<CharacterClass>);
+
+impl FromWorld for CharacterClasses {
+    fn from_world(world: &mut World) -> Self {
+        let definitions: Vec<ClassDefinition> = load_data("classes.ron");
+        let asset_server = world.resource::<AssetServer>().clone();
+        let mut atlases = world.resource_mut::<Assets<TextureAtlas>>();
+
+        CharacterClasses(
+            definitions
+                .into_iter()
+                .map(|definition| CharacterClass {
+                    assets: PlayerAssets::load(&definition.sprites, &asset_server, &mut atlases),
+                    definition,
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Added to freshly joined players, they can not move until a class is picked
+#[derive(Component, Default)]
+pub struct ChoosingClass {
+    pub selected: usize,
+}
+
+#[derive(Component)]
+pub struct ClassLabel;
+
+#[derive(Component)]
+pub struct PlayerClass {
+    pub index: usize,
+    pub timer: Timer,
+}
+
+pub fn spawn_class_labels(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    classes: Res<CharacterClasses>,
+    players: Query<(Entity, &ChoosingClass), Added<ChoosingClass>>,
+) {
+    for (player, choosing) in &players {
+        commands.entity(player).with_children(|parent| {
+            parent.spawn((
+                Text2dBundle {
+                    text: Text::from_section(
+                        format!("< {} >", classes.0[choosing.selected].definition.name),
+                        TextStyle {
+                            font: asset_server.load("fonts/Extended_font.ttf"),
+                            font_size: 20.0,
+                            color: Color::WHITE,
+                        },
+                    ),
+                    transform: Transform::from_xyz(0., 24., 100.).with_scale(Vec3::splat(0.25)),
+                    ..default()
+                },
+                ClassLabel,
+            ));
+        });
+    }
+}
+
+type ChoosingPlayer<'a> = (
+    Entity,
+    &'a ActionState<PlayerActions>,
+    &'a Transform,
+    &'a Children,
+    &'a mut ChoosingClass,
+    &'a mut AnimationStateMachine,
+    &'a mut AnimationState,
+    &'a mut Handle<TextureAtlas>,
+    &'a mut TextureAtlasSprite,
+);
+
+pub fn choose_class(
+    mut commands: Commands,
+    classes: Res<CharacterClasses>,
+    abilities: Res<Abilities>,
+    items: Res<Items>,
+    mut ev_pickup_i: EventWriter<PickupItemEvent>,
+    mut ev_pickup_w: EventWriter<PickupWeaponEvent>,
+    mut ev_healed: EventWriter<PlayerHealed>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<Outline>>,
+    gun_assets: Res<GunAssets>,
+    mut players: Query<ChoosingPlayer>,
+    mut stats: Query<(
+        &mut BaseStats,
+        &mut PlayerStats,
+        &mut StatModifiers,
+        &mut Inventory,
+    )>,
+    mut labels: Query<&mut Text, With<ClassLabel>>,
+) {
+    for (
+        entity,
+        actions,
+        transform,
+        children,
+        mut choosing,
+        mut machine,
+        mut state,
+        mut atlas,
+        mut sprite,
+    ) in &mut players
+    {
+        let count = classes.0.len();
+
+        if actions.just_pressed(PlayerActions::NextWeapon) {
+            choosing.selected = (choosing.selected + 1) % count;
+        } else if actions.just_pressed(PlayerActions::LastWeapon) {
+            choosing.selected = (choosing.selected + count - 1) % count;
+        } else if actions.just_pressed(PlayerActions::Pickup) {
+            let class = &classes.0[choosing.selected].definition;
+
+            if let Ok((mut base, mut current, mut modifiers, mut inventory)) = stats.get_mut(entity)
+            {
+                let previous_health = current.current_health;
+
+                base.0 = class.base_stats();
+                *current = base.0.clone();
+                ev_healed.send(PlayerHealed {
+                    target: entity,
+                    amount: current.current_health - previous_health,
+                });
+                modifiers.set_changed();
+                for item in class.items.iter().filter_map(|name| items.find(name)) {
+                    if inventory.add(item, &items) {
+                        ev_pickup_i.send(PickupItemEvent(item, entity));
+                    }
+                }
+            }
+            if let Some(gun) = class.weapon {
+                let gun_id = commands
+                    .spawn(gun.to_pickup(
+                        transform.translation.truncate(),
+                        &mut meshes,
+                        &mut materials,
+                        &gun_assets,
+                    ))
+                    .id();
+                ev_pickup_w.send(PickupWeaponEvent(entity, gun_id));
+            }
+            if let Some(ability) = class.ability.as_ref().and_then(|name| abilities.find(name)) {
+                commands
+                    .entity(entity)
+                    .insert(AbilitySlot::new(ability, &abilities));
+            }
+            for child in children {
+                if labels.contains(*child) {
+                    commands.entity(*child).despawn_recursive();
+                }
+            }
+            commands
+                .entity(entity)
+                .remove::<ChoosingClass>()
+                .insert(PlayerClass {
+                    index: choosing.selected,
+                    timer: Timer::from_seconds(1., TimerMode::Repeating),
+                });
+            continue;
+        } else {
+            continue;
+        }
+
+        let class = &classes.0[choosing.selected];
+        *machine = player_animations(&class.assets);
+        *state = AnimationState::new(&PlayerState::Idle);
+        *atlas = class.assets.idle.clone();
+        sprite.color = class.assets.tint;
+        for child in children {
+            if let Ok(mut text) = labels.get_mut(*child) {
+                text.sections[0].value = format!("< {} >", class.definition.name);
+            }
+        }
+    }
+}
+
+pub fn apply_passives(
+    time: Res<Time>,
+    classes: Res<CharacterClasses>,
+    mut ev_healed: EventWriter<PlayerHealed>,
+    mut players: Query<(
+        Entity,
+        &mut PlayerClass,
+        &mut PlayerStats,
+        &mut StatModifiers,
+        Option<&GunEntity>,
+    )>,
+    mut guns: Query<&mut GunStats, Without<PlayerStats>>,
+) {
+    for (entity, mut class, mut stats, mut modifiers, gun_entity) in &mut players {
+        match classes.0[class.index].definition.passive {
+            Passive::Regeneration(per_second) => {
+                if stats.current_health > 0. && stats.current_health < stats.max_health {
+                    let amount = stats.heal(per_second * time.delta_seconds());
+
+                    ev_healed.send(PlayerHealed {
+                        target: entity,
+                        amount,
+                    });
+                }
+            }
+            Passive::Adrenaline { threshold, speed } => {
+                let source = ModifierSource::Status("adrenaline".to_string());
+                let active = stats.current_health < stats.max_health * threshold;
+
+                if active && !modifiers.has(&source) {
+                    modifiers.add(
+                        source,
+                        vec![(Stat::Speed, ModifierKind::Multiplicative(speed))],
+                    );
+                } else if !active && modifiers.has(&source) {
+                    modifiers.remove_all(&source);
+                }
+            }
+            Passive::Resupply { interval } => {
+                class.timer.set_duration(Duration::from_secs_f32(interval));
+                if class.timer.tick(time.delta()).just_finished() {
+                    if let Some(mut gun) = gun_entity.and_then(|gun| guns.get_mut(gun.0).ok()) {
+                        if !gun.infinite && gun.ammo < gun.max_ammo {
+                            gun.ammo += 1;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
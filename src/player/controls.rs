@@ -0,0 +1,377 @@
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    input::{IsController, PlayerActions},
+    join::{PlayerIndex, MAX_PLAYERS},
+};
+
+const CONTROLS_FILE: &str = "controls.ron";
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LayoutPreset {
+    Qwerty,
+    Azerty,
+    LeftHanded,
+}
+
+impl LayoutPreset {
+    pub fn next(self) -> Self {
+        match self {
+            LayoutPreset::Qwerty => LayoutPreset::Azerty,
+            LayoutPreset::Azerty => LayoutPreset::LeftHanded,
+            LayoutPreset::LeftHanded => LayoutPreset::Qwerty,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Wheel {
+    Up,
+    Down,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Wheel(Wheel),
+    Button(GamepadButtonType),
+    LeftStick,
+    RightStick,
+}
+
+impl Binding {
+    /// Short name shown in button prompts
+    pub fn label(&self) -> String {
+        match self {
+            Binding::Key(key) => format!("{key:?}"),
+            Binding::Mouse(button) => format!("Mouse {button:?}"),
+            Binding::Wheel(wheel) => format!("Wheel {wheel:?}"),
+            Binding::Button(button) => format!("{button:?}"),
+            Binding::LeftStick => "Left stick".to_string(),
+            Binding::RightStick => "Right stick".to_string(),
+        }
+    }
+}
+
+pub type Bindings = BTreeMap<PlayerActions, Vec<Binding>>;
+
+fn bindings<const COUNT: usize>(content: [(PlayerActions, Binding); COUNT]) -> Bindings {
+    let mut bindings = Bindings::new();
+
+    for (action, binding) in content {
+        bindings.entry(action).or_default().push(binding);
+    }
+    bindings
+}
+
+pub fn keyboard_preset(preset: LayoutPreset) -> Bindings {
+    use Binding::*;
+
+    let mouse = [
+        (PlayerActions::Shoot, Mouse(MouseButton::Left)),
+        (PlayerActions::Ability, Mouse(MouseButton::Right)),
+        (PlayerActions::NextWeapon, Wheel(self::Wheel::Up)),
+        (PlayerActions::LastWeapon, Wheel(self::Wheel::Down)),
+        (PlayerActions::Leave, Key(KeyCode::Back)),
+    ];
+    let keys = match preset {
+        LayoutPreset::Qwerty => [
+            (PlayerActions::Left, Key(KeyCode::A)),
+            (PlayerActions::Right, Key(KeyCode::D)),
+            (PlayerActions::Up, Key(KeyCode::W)),
+            (PlayerActions::Down, Key(KeyCode::S)),
+            (PlayerActions::Roll, Key(KeyCode::Space)),
+            (PlayerActions::Reload, Key(KeyCode::R)),
+            (PlayerActions::Pickup, Key(KeyCode::E)),
+            (PlayerActions::DropItem, Key(KeyCode::Z)),
+            (PlayerActions::DropWeapon, Key(KeyCode::X)),
+            (PlayerActions::LastWeapon, Key(KeyCode::Key1)),
+            (PlayerActions::NextWeapon, Key(KeyCode::Key2)),
+            (PlayerActions::LastItem, Key(KeyCode::Key3)),
+            (PlayerActions::NextItem, Key(KeyCode::Key4)),
+            (PlayerActions::UseItem, Key(KeyCode::Q)),
+            (PlayerActions::Ability, Key(KeyCode::F)),
+        ],
+        LayoutPreset::Azerty => [
+            (PlayerActions::Left, Key(KeyCode::Q)),
+            (PlayerActions::Right, Key(KeyCode::D)),
+            (PlayerActions::Up, Key(KeyCode::Z)),
+            (PlayerActions::Down, Key(KeyCode::S)),
+            (PlayerActions::Roll, Key(KeyCode::Space)),
+            (PlayerActions::Reload, Key(KeyCode::R)),
+            (PlayerActions::Pickup, Key(KeyCode::E)),
+            (PlayerActions::DropItem, Key(KeyCode::W)),
+            (PlayerActions::DropWeapon, Key(KeyCode::X)),
+            (PlayerActions::LastWeapon, Key(KeyCode::Key1)),
+            (PlayerActions::NextWeapon, Key(KeyCode::Key2)),
+            (PlayerActions::LastItem, Key(KeyCode::Key3)),
+            (PlayerActions::NextItem, Key(KeyCode::Key4)),
+            (PlayerActions::UseItem, Key(KeyCode::A)),
+            (PlayerActions::Ability, Key(KeyCode::F)),
+        ],
+        LayoutPreset::LeftHanded => [
+            (PlayerActions::Left, Key(KeyCode::Left)),
+            (PlayerActions::Right, Key(KeyCode::Right)),
+            (PlayerActions::Up, Key(KeyCode::Up)),
+            (PlayerActions::Down, Key(KeyCode::Down)),
+            (PlayerActions::Roll, Key(KeyCode::ShiftRight)),
+            (PlayerActions::Reload, Key(KeyCode::ControlRight)),
+            (PlayerActions::Pickup, Key(KeyCode::Slash)),
+            (PlayerActions::DropItem, Key(KeyCode::Period)),
+            (PlayerActions::DropWeapon, Key(KeyCode::Comma)),
+            (PlayerActions::LastWeapon, Key(KeyCode::BracketLeft)),
+            (PlayerActions::NextWeapon, Key(KeyCode::BracketRight)),
+            (PlayerActions::LastItem, Key(KeyCode::Minus)),
+            (PlayerActions::NextItem, Key(KeyCode::Equals)),
+            (PlayerActions::UseItem, Key(KeyCode::Semicolon)),
+            (PlayerActions::Ability, Key(KeyCode::Apostrophe)),
+        ],
+    };
+
+    let mut result = bindings(keys);
+    for (action, binding) in mouse {
+        result.entry(action).or_default().push(binding);
+    }
+    result
+}
+
+pub fn gamepad_preset() -> Bindings {
+    use Binding::*;
+
+    bindings([
+        (PlayerActions::ControllerMove, LeftStick),
+        (PlayerActions::ControllerLook, RightStick),
+        (
+            PlayerActions::Shoot,
+            Button(GamepadButtonType::RightTrigger2),
+        ),
+        (PlayerActions::Roll, Button(GamepadButtonType::LeftTrigger2)),
+        (PlayerActions::Reload, Button(GamepadButtonType::North)),
+        (PlayerActions::Pickup, Button(GamepadButtonType::South)),
+        (PlayerActions::DropItem, Button(GamepadButtonType::West)),
+        (PlayerActions::DropWeapon, Button(GamepadButtonType::Select)),
+        (
+            PlayerActions::NextWeapon,
+            Button(GamepadButtonType::DPadRight),
+        ),
+        (
+            PlayerActions::LastWeapon,
+            Button(GamepadButtonType::DPadLeft),
+        ),
+        (PlayerActions::NextItem, Button(GamepadButtonType::DPadUp)),
+        (PlayerActions::LastItem, Button(GamepadButtonType::DPadDown)),
+        (PlayerActions::UseItem, Button(GamepadButtonType::LeftTrigger)),
+        (
+            PlayerActions::Ability,
+            Button(GamepadButtonType::RightTrigger),
+        ),
+        (PlayerActions::Leave, Button(GamepadButtonType::Start)),
+    ])
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ControlProfile {
+    pub name: String,
+    pub layout: LayoutPreset,
+    pub keyboard: Bindings,
+    pub gamepad: Bindings,
+}
+
+impl ControlProfile {
+    pub fn new(name: String, layout: LayoutPreset) -> Self {
+        ControlProfile {
+            name,
+            layout,
+            keyboard: keyboard_preset(layout),
+            gamepad: gamepad_preset(),
+        }
+    }
+
+    /// Binds the actions missing from an older controls file to their defaults
+    pub fn fill_missing(&mut self) {
+        for (action, bindings) in keyboard_preset(self.layout) {
+            self.keyboard.entry(action).or_insert(bindings);
+        }
+        for (action, bindings) in gamepad_preset() {
+            self.gamepad.entry(action).or_insert(bindings);
+        }
+    }
+
+    /// Switches to another preset, actions rebound by hand in `controls.ron` keep their bindings
+    pub fn set_layout(&mut self, layout: LayoutPreset) {
+        let previous = keyboard_preset(self.layout);
+        let mut keyboard = keyboard_preset(layout);
+
+        for (action, bindings) in &self.keyboard {
+            if previous.get(action) != Some(bindings) {
+                keyboard.insert(*action, bindings.clone());
+            }
+        }
+        self.layout = layout;
+        self.keyboard = keyboard;
+    }
+
+    /// Label of the first input bound to `action`, for button prompts
+    pub fn button_label(&self, gamepad: bool, action: PlayerActions) -> String {
+        let device = if gamepad {
+            &self.gamepad
+        } else {
+            &self.keyboard
+        };
+        device
+            .get(&action)
+            .and_then(|bindings| bindings.first())
+            .map_or_else(|| "?".to_string(), Binding::label)
+    }
+
+    pub fn input_map(&self, gamepad: Option<Gamepad>) -> InputMap<PlayerActions> {
+        let mut input_map = InputMap::default();
+        let device = if gamepad.is_some() {
+            &self.gamepad
+        } else {
+            &self.keyboard
+        };
+
+        for (action, inputs) in device {
+            for binding in inputs {
+                insert_binding(&mut input_map, *binding, *action);
+            }
+        }
+        if let Some(gamepad) = gamepad {
+            input_map.set_gamepad(gamepad);
+        }
+        input_map
+    }
+}
+
+fn insert_binding(
+    input_map: &mut InputMap<PlayerActions>,
+    binding: Binding,
+    action: PlayerActions,
+) {
+    match binding {
+        Binding::Key(key) => input_map.insert(key, action),
+        Binding::Mouse(button) => input_map.insert(button, action),
+        Binding::Wheel(Wheel::Up) => input_map.insert(MouseWheelDirection::Up, action),
+        Binding::Wheel(Wheel::Down) => input_map.insert(MouseWheelDirection::Down, action),
+        Binding::Button(button) => input_map.insert(button, action),
+        Binding::LeftStick => input_map.insert(DualAxis::left_stick(), action),
+        Binding::RightStick => input_map.insert(DualAxis::right_stick(), action),
+    };
+}
+
+/// Bindings of every local player, indexed by [`PlayerIndex`].
+#[derive(Resource, Serialize, Deserialize, Clone, Debug)]
+pub struct ControlsConfig {
+    pub profiles: Vec<ControlProfile>,
+}
+
+impl Default for ControlsConfig {
+    fn default() -> Self {
+        ControlsConfig {
+            profiles: (0..MAX_PLAYERS)
+                .map(|index| {
+                    ControlProfile::new(format!("Player {}", index + 1), LayoutPreset::Azerty)
+                })
+                .collect(),
+        }
+    }
+}
+
+impl ControlsConfig {
+    pub fn path() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_default()
+            .join("HyperBlast")
+            .join(CONTROLS_FILE)
+    }
+
+    pub fn load() -> Self {
+        let path = Self::path();
+        let mut config = match fs::read_to_string(&path) {
+            Ok(content) => ron::from_str::<ControlsConfig>(&content).unwrap_or_else(|err| {
+                warn!("Invalid controls file {}: {err}", path.display());
+                ControlsConfig::default()
+            }),
+            Err(_) => ControlsConfig::default(),
+        };
+
+        for profile in &mut config.profiles {
+            profile.fill_missing();
+        }
+        while config.profiles.len() < MAX_PLAYERS {
+            let name = format!("Player {}", config.profiles.len() + 1);
+            config
+                .profiles
+                .push(ControlProfile::new(name, LayoutPreset::Azerty));
+        }
+        config
+    }
+
+    pub fn save(&self) {
+        let path = Self::path();
+        let content = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .expect("Controls should always be serializable");
+
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        if let Err(err) = fs::write(&path, content) {
+            warn!("Could not save controls to {}: {err}", path.display());
+        }
+    }
+
+    pub fn profile(&self, index: usize) -> &ControlProfile {
+        &self.profiles[index % self.profiles.len()]
+    }
+
+    pub fn profile_mut(&mut self, index: usize) -> &mut ControlProfile {
+        let len = self.profiles.len();
+        &mut self.profiles[index % len]
+    }
+}
+
+pub fn apply_controls(
+    controls: Res<ControlsConfig>,
+    mut players: Query<(
+        &PlayerIndex,
+        Option<&IsController>,
+        &mut InputMap<PlayerActions>,
+    )>,
+) {
+    if !controls.is_changed() {
+        return;
+    }
+    for (index, controller, mut input_map) in &mut players {
+        *input_map = controls
+            .profile(index.0)
+            .input_map(controller.map(|controller| controller.0));
+    }
+}
+
+pub fn save_controls(controls: Res<ControlsConfig>) {
+    if controls.is_changed() && !controls.is_added() {
+        controls.save();
+    }
+}
+
+pub fn cycle_keyboard_layout(
+    input: Res<Input<KeyCode>>,
+    mut controls: ResMut<ControlsConfig>,
+    players: Query<&PlayerIndex, Without<IsController>>,
+) {
+    if input.just_pressed(KeyCode::F2) {
+        for index in &players {
+            let profile = controls.profile_mut(index.0);
+            let layout = profile.layout.next();
+
+            profile.set_layout(layout);
+            info!("{} now uses the {:?} layout", profile.name, layout);
+        }
+    }
+}
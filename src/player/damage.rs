@@ -0,0 +1,212 @@
+use bevy::{prelude::*, utils::HashMap};
+use serde::Deserialize;
+
+use super::{
+    bullets::BulletType,
+    downed::PlayerDowned,
+    inventory::weapon_manager::Guns,
+    join::PlayerIndex,
+    roll::RollStats,
+    stats::{PlayerStats, ShieldCooldown},
+    team::{Team, TeamMode, TeamRules},
+};
+
+pub const CRIT_MULTIPLIER: f32 = 2.;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Reflect, Deserialize)]
+pub enum DamageType {
+    Ballistic,
+    Fire,
+}
+
+impl From<BulletType> for DamageType {
+    fn from(bullet_type: BulletType) -> Self {
+        match bullet_type {
+            BulletType::Reagular => DamageType::Ballistic,
+            BulletType::Flame => DamageType::Fire,
+        }
+    }
+}
+
+/// Request to hurt a player, resolved by `apply_damage`
+#[derive(Event, Clone, Debug)]
+pub struct DamageEvent {
+    pub target: Entity,
+    pub source: Option<Entity>,
+    pub weapon: Option<Guns>,
+    pub damage_type: DamageType,
+    pub amount: f32,
+    pub position: Vec2,
+    pub crit: bool,
+}
+
+/// Sent once the damages went through, `amount` is what was actually dealt
+#[derive(Event, Clone, Debug)]
+pub struct PlayerDamaged {
+    pub target: Entity,
+    pub source: Option<Entity>,
+    pub weapon: Option<Guns>,
+    pub damage_type: DamageType,
+    pub amount: f32,
+    pub position: Vec2,
+    pub crit: bool,
+}
+
+/// Sent when health changes outside of damages: heals, revives and max health changes,
+/// `amount` is negative when the max health drops
+#[derive(Event, Clone, Debug)]
+pub struct PlayerHealed {
+    pub target: Entity,
+    pub amount: f32,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct PlayerKilled {
+    pub target: Entity,
+    pub killer: Option<Entity>,
+    pub weapon: Option<Guns>,
+    pub position: Vec2,
+}
+
+/// Fraction of each damage type ignored by the player
+#[derive(Component, Default, Clone, Debug)]
+pub struct Resistances(pub HashMap<DamageType, f32>);
+
+impl Resistances {
+    pub fn multiplier(&self, damage_type: DamageType) -> f32 {
+        1. - self
+            .0
+            .get(&damage_type)
+            .copied()
+            .unwrap_or(0.)
+            .clamp(0., 1.)
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug)]
+pub struct Score {
+    pub kills: usize,
+    pub deaths: usize,
+    pub damages_dealt: f32,
+    pub damages_taken: f32,
+}
+
+/// Scores by player index, kept when players die or leave
+#[derive(Resource, Default)]
+pub struct Scoreboard(pub HashMap<usize, Score>);
+
+/// Whether a hit from `source` can touch `target` at all,
+/// bullets go through the players it can't
+pub fn can_hit(
+    rules: &TeamRules,
+    source: Option<&Team>,
+    target: Option<&Team>,
+    stats: &PlayerStats,
+    roll: Option<&RollStats>,
+) -> bool {
+    let invulnerable = roll.map_or(false, |roll| roll.is_invulnerable(stats));
+
+    !invulnerable && rules.damage_multiplier(source, target).is_some()
+}
+
+pub fn apply_damage(
+    mut commands: Commands,
+    rules: Res<TeamRules>,
+    mut damages: EventReader<DamageEvent>,
+    mut ev_damaged: EventWriter<PlayerDamaged>,
+    mut ev_killed: EventWriter<PlayerKilled>,
+    mut ev_downed: EventWriter<PlayerDowned>,
+    mut players: Query<(&mut PlayerStats, Option<&RollStats>, Option<&Resistances>)>,
+    teams: Query<&Team>,
+) {
+    for damage in damages.iter() {
+        let Ok((mut stats, roll, resistances)) = players.get_mut(damage.target) else {
+            continue;
+        };
+        if stats.current_health <= 0. {
+            continue;
+        }
+        let source_team = damage.source.and_then(|source| teams.get(source).ok());
+        let target_team = teams.get(damage.target).ok();
+
+        if !can_hit(&rules, source_team, target_team, &stats, roll) {
+            continue;
+        }
+        let mut amount = damage.amount
+            * rules
+                .damage_multiplier(source_team, target_team)
+                .unwrap_or(1.)
+            * resistances.map_or(1., |resistances| resistances.multiplier(damage.damage_type));
+        if damage.crit {
+            amount *= CRIT_MULTIPLIER;
+        }
+
+        let previous_total = stats.current_health + stats.current_shield;
+        stats.take_damage(amount);
+        commands
+            .entity(damage.target)
+            .insert(ShieldCooldown(Timer::new(
+                stats.shield_regen_delay,
+                TimerMode::Once,
+            )));
+
+        ev_damaged.send(PlayerDamaged {
+            target: damage.target,
+            source: damage.source,
+            weapon: damage.weapon,
+            damage_type: damage.damage_type,
+            amount: previous_total - (stats.current_health + stats.current_shield),
+            position: damage.position,
+            crit: damage.crit,
+        });
+        if stats.current_health <= 0. {
+            if rules.mode == TeamMode::Coop {
+                ev_downed.send(PlayerDowned {
+                    target: damage.target,
+                    source: damage.source,
+                    weapon: damage.weapon,
+                    position: damage.position,
+                });
+            } else {
+                ev_killed.send(PlayerKilled {
+                    target: damage.target,
+                    killer: damage.source,
+                    weapon: damage.weapon,
+                    position: damage.position,
+                });
+            }
+        }
+    }
+}
+
+pub fn update_scoreboard(
+    mut scoreboard: ResMut<Scoreboard>,
+    mut ev_damaged: EventReader<PlayerDamaged>,
+    mut ev_killed: EventReader<PlayerKilled>,
+    players: Query<&PlayerIndex>,
+) {
+    for damaged in ev_damaged.iter() {
+        if let Ok(target) = players.get(damaged.target) {
+            scoreboard.0.entry(target.0).or_default().damages_taken += damaged.amount;
+        }
+        if let Some(source) = damaged.source.and_then(|source| players.get(source).ok()) {
+            if damaged.source != Some(damaged.target) {
+                scoreboard.0.entry(source.0).or_default().damages_dealt += damaged.amount;
+            }
+        }
+    }
+    for killed in ev_killed.iter() {
+        if let Ok(target) = players.get(killed.target) {
+            scoreboard.0.entry(target.0).or_default().deaths += 1;
+        }
+        if let Some(killer) = killed.killer.and_then(|killer| players.get(killer).ok()) {
+            if killed.killer != Some(killed.target) {
+                scoreboard.0.entry(killer.0).or_default().kills += 1;
+            }
+        }
+        info!(
+            "{:?} killed {:?} with {:?}",
+            killed.killer, killed.target, killed.weapon
+        );
+    }
+}
@@ -19,13 +19,30 @@ pub struct MoveDirection {
     pub value: Vec2,
 }
 
+/// Angle in degrees between `value` and the bottom of the screen, in [0, 360[
+pub fn angle_from_front(value: Vec2) -> f32 {
+    let mut angle = value.angle_between(Vec2::NEG_Y).to_degrees();
+    if angle < 0. {
+        angle += 360.
+    }
+    angle
+}
+
+/// Smallest difference in degrees between two angles, in [0, 180]
+pub fn angle_difference(a: f32, b: f32) -> f32 {
+    let difference = (a - b).abs() % 360.;
+    difference.min(360. - difference)
+}
+
+impl CursorPosition {
+    pub fn to_angle(&self) -> f32 {
+        angle_from_front(self.relative)
+    }
+}
+
 impl MoveDirection {
     pub fn to_angle(&self) -> f32 {
-        let mut angle = self.value.angle_between(Vec2::NEG_Y).to_degrees();
-        if angle < 0. {
-            angle += 360.
-        }
-        angle
+        angle_from_front(self.value)
     }
 }
 
@@ -0,0 +1,248 @@
+use std::f32::consts::TAU;
+
+use bevy::{
+    math::Vec3Swizzles,
+    prelude::*,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+};
+use leafwing_input_manager::prelude::ActionState;
+
+use super::{
+    damage::{PlayerHealed, PlayerKilled},
+    input::PlayerActions,
+    interact::{Interactable, InteractionTarget},
+    inventory::weapon_manager::Guns,
+    modifiers::{ModifierKind, ModifierSource, StatModifiers},
+    stats::{PlayerStats, Stat},
+    team::Team,
+};
+
+const BLEED_OUT_DURATION: f32 = 20.;
+const REVIVE_DURATION: f32 = 3.;
+const REVIVE_RANGE: f32 = 25.;
+const REVIVE_HEALTH: f32 = 0.3;
+const CRAWL_SPEED: f32 = 0.3;
+const RING_RADIUS: f32 = 14.;
+const RING_SEGMENTS: usize = 24;
+const RING_WIDTH: f32 = 1.5;
+
+/// Sent instead of `PlayerKilled` when a co-op player runs out of health
+#[derive(Event, Clone, Debug)]
+pub struct PlayerDowned {
+    pub target: Entity,
+    pub source: Option<Entity>,
+    pub weapon: Option<Guns>,
+    pub position: Vec2,
+}
+
+#[derive(Event, Clone, Debug)]
+pub struct PlayerRevived {
+    pub target: Entity,
+    pub reviver: Entity,
+}
+
+/// The player crawls with a sidearm until revived or bled out
+#[derive(Component)]
+pub struct Downed {
+    pub bleed_out: Timer,
+    pub revive_progress: f32,
+    pub source: Option<Entity>,
+    pub weapon: Option<Guns>,
+}
+
+/// Arc around a downed player, shrinking while they bleed out or filling up while revived
+#[derive(Component, Clone, Copy)]
+pub enum ReviveRing {
+    BleedOut,
+    Revive,
+}
+
+impl ReviveRing {
+    fn radius(self) -> f32 {
+        match self {
+            ReviveRing::BleedOut => RING_RADIUS,
+            ReviveRing::Revive => RING_RADIUS + 2.,
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            ReviveRing::BleedOut => Color::RED,
+            ReviveRing::Revive => Color::LIME_GREEN,
+        }
+    }
+}
+
+fn downed_source() -> ModifierSource {
+    ModifierSource::Status("downed".to_string())
+}
+
+pub fn down_players(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut ev_downed: EventReader<PlayerDowned>,
+    mut players: Query<&mut StatModifiers>,
+) {
+    for downed in ev_downed.iter() {
+        if let Ok(mut modifiers) = players.get_mut(downed.target) {
+            modifiers.add(
+                downed_source(),
+                vec![(Stat::Speed, ModifierKind::Multiplicative(CRAWL_SPEED))],
+            );
+            commands.entity(downed.target).insert((
+                Downed {
+                    bleed_out: Timer::from_seconds(BLEED_OUT_DURATION, TimerMode::Once),
+                    revive_progress: 0.,
+                    source: downed.source,
+                    weapon: downed.weapon,
+                },
+                Interactable::new("Hold to revive")
+                    .with_range(REVIVE_RANGE)
+                    .with_priority(1),
+            ));
+            commands.entity(downed.target).with_children(|parent| {
+                for ring in [ReviveRing::BleedOut, ReviveRing::Revive] {
+                    parent.spawn((
+                        Name::new("Revive ring"),
+                        MaterialMesh2dBundle {
+                            mesh: meshes.add(ring_mesh(1., ring.radius())).into(),
+                            material: materials.add(ColorMaterial::from(ring.color())),
+                            transform: Transform::from_xyz(0., 6., 1.),
+                            ..default()
+                        },
+                        ring,
+                    ));
+                }
+            });
+        }
+    }
+}
+
+pub fn bleed_out(
+    time: Res<Time>,
+    mut ev_killed: EventWriter<PlayerKilled>,
+    mut players: Query<(Entity, &Transform, &mut Downed)>,
+) {
+    for (entity, transform, mut downed) in &mut players {
+        // Bleeding pauses while someone is reviving
+        if downed.revive_progress == 0. && downed.bleed_out.tick(time.delta()).just_finished() {
+            ev_killed.send(PlayerKilled {
+                target: entity,
+                killer: downed.source,
+                weapon: downed.weapon,
+                position: transform.translation.xy(),
+            });
+        }
+    }
+}
+
+pub fn revive_players(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut ev_revived: EventWriter<PlayerRevived>,
+    mut ev_healed: EventWriter<PlayerHealed>,
+    mut downed_players: Query<(
+        Entity,
+        Option<&Team>,
+        &mut Downed,
+        &mut PlayerStats,
+        &mut StatModifiers,
+    )>,
+    revivers: Query<
+        (
+            Entity,
+            &InteractionTarget,
+            Option<&Team>,
+            &ActionState<PlayerActions>,
+        ),
+        Without<Downed>,
+    >,
+) {
+    for (entity, team, mut downed, mut stats, mut modifiers) in &mut downed_players {
+        let reviver = revivers.iter().find(|(_, target, reviver_team, actions)| {
+            actions.pressed(PlayerActions::Pickup)
+                && *reviver_team == team
+                && target.0 == Some(entity)
+        });
+
+        let Some((reviver, ..)) = reviver else {
+            downed.revive_progress = 0.;
+            continue;
+        };
+        downed.revive_progress += time.delta_seconds();
+        if downed.revive_progress >= REVIVE_DURATION {
+            modifiers.remove_all(&downed_source());
+            let previous = stats.current_health;
+            stats.current_health = stats.max_health * REVIVE_HEALTH;
+            ev_healed.send(PlayerHealed {
+                target: entity,
+                amount: stats.current_health - previous,
+            });
+            commands
+                .entity(entity)
+                .remove::<Downed>()
+                .remove::<Interactable>();
+            ev_revived.send(PlayerRevived {
+                target: entity,
+                reviver,
+            });
+        }
+    }
+}
+
+/// First `fraction` of a ring, counterclockwise from the top
+fn ring_mesh(fraction: f32, radius: f32) -> Mesh {
+    let segments = (fraction * RING_SEGMENTS as f32).ceil() as u32;
+    let mut positions = Vec::new();
+    let mut indices = Vec::new();
+
+    for i in 0..=segments {
+        let angle = TAU * (i as f32 / RING_SEGMENTS as f32).min(fraction);
+        let direction = Vec2::from_angle(angle).perp();
+
+        for edge in [radius - RING_WIDTH / 2., radius + RING_WIDTH / 2.] {
+            positions.push([direction.x * edge, direction.y * edge, 0.]);
+        }
+    }
+    for i in 0..segments {
+        indices.extend([2 * i, 2 * i + 1, 2 * i + 2, 2 * i + 1, 2 * i + 3, 2 * i + 2]);
+    }
+    let count = positions.len();
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0., 0., 1.]; count]);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, vec![[0., 0.]; count]);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
+/// Redraws the rings of downed players, removing them once their player is revived
+pub fn update_revive_rings(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    players: Query<&Downed>,
+    mut rings: Query<(Entity, &ReviveRing, &Parent, &Mesh2dHandle, &mut Visibility)>,
+) {
+    for (entity, ring, parent, mesh, mut visibility) in &mut rings {
+        let Ok(downed) = players.get(parent.get()) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+        let fraction = match ring {
+            ReviveRing::BleedOut => downed.bleed_out.percent_left(),
+            ReviveRing::Revive => downed.revive_progress / REVIVE_DURATION,
+        };
+
+        *visibility = if fraction > 0. {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if let Some(mesh) = meshes.get_mut(&mesh.0) {
+            *mesh = ring_mesh(fraction, ring.radius());
+        }
+    }
+}
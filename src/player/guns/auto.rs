@@ -6,7 +6,7 @@ use crate::{
     player::{
         inventory::{
             pickup::GunPickupBundle,
-            weapon_manager::GunAssets,
+            weapon_manager::{GunAssets, Guns},
         },
         weapon::{auto_shoot_fn, basic_reload_fn, GunStats},
     },
@@ -15,6 +15,7 @@ use crate::{
 
 pub fn automatic_stats() -> GunStats {
     GunStats {
+        gun: Some(Guns::Auto),
         handle_position: Vec2::new(14., 4.),
         size: Vec2::new(30., 9.),
         barrel_length: 17.,
@@ -6,7 +6,7 @@ use crate::{
     player::{
         inventory::{
             pickup::GunPickupBundle,
-            weapon_manager::GunAssets,
+            weapon_manager::{GunAssets, Guns},
         },
         weapon::{charging_shoot_fn, basic_reload_fn, GunStats},
     },
@@ -15,6 +15,7 @@ use crate::{
 
 pub fn charged_stats() -> GunStats {
     GunStats {
+        gun: Some(Guns::Charge),
         ammo: 50,
         distance: 100.,
         damage: 20.,
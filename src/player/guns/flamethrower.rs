@@ -6,7 +6,7 @@ use crate::{
     player::{
         inventory::{
             pickup::GunPickupBundle,
-            weapon_manager::GunAssets,
+            weapon_manager::{GunAssets, Guns},
         },
         weapon::{overheat_shoot_fn, no_reload, GunStats}, bullets::BulletType,
     },
@@ -15,6 +15,7 @@ use crate::{
 
 pub fn flamethrower_stats() -> GunStats {
     GunStats {
+        gun: Some(Guns::Flamethrower),
         handle_position: Vec2::new(1., 1.),
         size: Vec2::new(16., 5.),
         barrel_length: 18.,
@@ -6,7 +6,7 @@ use crate::{
     player::{
         inventory::{
             pickup::GunPickupBundle,
-            weapon_manager::GunAssets,
+            weapon_manager::{GunAssets, Guns},
         },
         weapon::{auto_shoot_fn, basic_reload_fn, GunStats},
     },
@@ -15,6 +15,7 @@ use crate::{
 
 pub fn revolver_stats() -> GunStats {
     GunStats {
+        gun: Some(Guns::Revolver),
         handle_position: Vec2::new(10., 3.),
         size: Vec2::new(14., 10.),
         shoot: auto_shoot_fn,
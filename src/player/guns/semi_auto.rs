@@ -6,7 +6,7 @@ use crate::{
     player::{
         inventory::{
             pickup::GunPickupBundle,
-            weapon_manager::GunAssets,
+            weapon_manager::{GunAssets, Guns},
         },
         weapon::{manual_shoot_fn, basic_reload_fn, GunStats},
     },
@@ -15,6 +15,7 @@ use crate::{
 
 pub fn semi_automatic_stats() -> GunStats {
     GunStats {
+        gun: Some(Guns::SemiAuto),
         handle_position: Vec2::new(10., 4.),
         size: Vec2::new(30., 8.),
         barrel_length: 20.,
@@ -6,7 +6,7 @@ use crate::{
     player::{
         inventory::{
             pickup::GunPickupBundle,
-            weapon_manager::GunAssets,
+            weapon_manager::{GunAssets, Guns},
         },
         weapon::{manual_shoot_fn, shotgun_reload_fn, GunStats},
     },
@@ -16,6 +16,7 @@ use crate::{
 
 pub fn shotgun_stats() -> GunStats {
     GunStats {
+        gun: Some(Guns::Shotgun),
         handle_position: Vec2::new(10., 3.),
         size: Vec2::new(27., 7.),
         barrel_length: 19.,
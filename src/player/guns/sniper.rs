@@ -6,7 +6,7 @@ use crate::{
     player::{
         inventory::{
             pickup::GunPickupBundle,
-            weapon_manager::GunAssets,
+            weapon_manager::{GunAssets, Guns},
         },
         weapon::{basic_reload_fn, manual_shoot_fn, GunStats},
     },
@@ -15,6 +15,7 @@ use crate::{
 
 pub fn sniper_stats() -> GunStats {
     GunStats {
+        gun: Some(Guns::Sniper),
         handle_position: Vec2::new(10., 4.),
         size: Vec2::new(30., 10.),
         barrel_length: 20.,
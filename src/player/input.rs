@@ -1,7 +1,9 @@
 use bevy::{math::Vec3Swizzles, prelude::*, reflect::TypePath};
 use bevy_rapier2d::prelude::Velocity;
 use leafwing_input_manager::{prelude::*, Actionlike};
+use serde::{Deserialize, Serialize};
 
+use crate::map::surface::SurfaceMap;
 use crate::rendering::utils::set_anchor;
 use crate::{animation::AnimationState, debug::DebugLevel, rendering::utils::Angle};
 
@@ -13,14 +15,30 @@ use crate::player::{
 };
 
 use super::{
-    direction::{CursorPosition, MoveDirection},
-    inventory::inventory_manager::Inventory,
+    class::ChoosingClass,
+    controls::ControlProfile,
+    direction::{angle_difference, CursorPosition, MoveDirection},
+    downed::Downed,
+    inventory::{inventory_manager::Inventory, weapon_manager::Guns},
 };
 
 #[derive(Component)]
-pub struct IsController;
+pub struct IsController(pub Gamepad);
 
-#[derive(Actionlike, PartialEq, Eq, Clone, Copy, Hash, Debug, TypePath)]
+#[derive(
+    Actionlike,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Copy,
+    Hash,
+    Debug,
+    TypePath,
+    Serialize,
+    Deserialize,
+)]
 pub enum PlayerActions {
     ControllerMove,
     ControllerLook,
@@ -36,9 +54,14 @@ pub enum PlayerActions {
     DropWeapon,
     NextWeapon,
     LastWeapon,
+    NextItem,
+    LastItem,
+    UseItem,
+    Ability,
+    Leave,
 }
 
-#[derive(Component, Debug, Reflect, Default)]
+#[derive(Component, Debug, Reflect, Default, Clone, Copy, PartialEq)]
 pub enum PlayerState {
     #[default]
     Idle,
@@ -48,6 +71,18 @@ pub enum PlayerState {
     RightBack,
     Front,
     Back,
+    StrafeLeftFront,
+    StrafeLeftBack,
+    StrafeRightFront,
+    StrafeRightBack,
+    StrafeFront,
+    StrafeBack,
+    BackpedalLeftFront,
+    BackpedalLeftBack,
+    BackpedalRightFront,
+    BackpedalRightBack,
+    BackpedalFront,
+    BackpedalBack,
     DodgeLeftFront,
     DodgeLeftBack,
     DodgeRightFront,
@@ -56,6 +91,50 @@ pub enum PlayerState {
     DodgeBack,
 }
 
+impl PlayerState {
+    /// Running state facing the given angle, see `direction::angle_from_front`
+    pub fn facing(angle: f32) -> Self {
+        match angle {
+            n if (n < 30. + 60. * 0.) => PlayerState::Front,
+            n if (n <= 30. + 60. * 1.) => PlayerState::LeftFront,
+            n if (n < 30. + 60. * 2.) => PlayerState::LeftBack,
+            n if (n < 30. + 60. * 3.) => PlayerState::Back,
+            n if (n < 30. + 60. * 4.) => PlayerState::RightBack,
+            n if (n <= 30. + 60. * 5.) => PlayerState::RightFront,
+            n if (n < 30. + 60. * 6.) => PlayerState::Front,
+            _ => {
+                panic!("IMPOSSIBLE ANGLE!")
+            }
+        }
+    }
+
+    /// Moving sideways while facing the same way
+    pub fn strafe(self) -> Self {
+        match self {
+            PlayerState::LeftFront => PlayerState::StrafeLeftFront,
+            PlayerState::LeftBack => PlayerState::StrafeLeftBack,
+            PlayerState::RightFront => PlayerState::StrafeRightFront,
+            PlayerState::RightBack => PlayerState::StrafeRightBack,
+            PlayerState::Front => PlayerState::StrafeFront,
+            PlayerState::Back => PlayerState::StrafeBack,
+            state => state,
+        }
+    }
+
+    /// Moving away from where the player is facing
+    pub fn backpedal(self) -> Self {
+        match self {
+            PlayerState::LeftFront => PlayerState::BackpedalLeftFront,
+            PlayerState::LeftBack => PlayerState::BackpedalLeftBack,
+            PlayerState::RightFront => PlayerState::BackpedalRightFront,
+            PlayerState::RightBack => PlayerState::BackpedalRightBack,
+            PlayerState::Front => PlayerState::BackpedalFront,
+            PlayerState::Back => PlayerState::BackpedalBack,
+            state => state,
+        }
+    }
+}
+
 pub fn update_gun_angle(
     debug_level: DebugLevel,
     lines: &mut bevy_prototype_debug_lines::DebugLines,
@@ -118,6 +197,7 @@ pub fn shooting_system(
         &CursorPosition,
         Option<&RollStats>,
         Option<&ReloadStats>,
+        Option<&Downed>,
     )>,
     mut gun: Query<(
         &GlobalTransform,
@@ -131,7 +211,7 @@ pub fn shooting_system(
     mut commands: Commands,
     gun_assets: Res<super::inventory::weapon_manager::GunAssets>,
 ) {
-    for (entity, gun_id, player_actions, mut stats, inv, cursor_position, roll, reload) in
+    for (entity, gun_id, player_actions, mut stats, inv, cursor_position, roll, reload, downed) in
         &mut players
     {
         if let Ok((gun_transform, mut gun_angle, mut sprite, mut gun_stats, _)) =
@@ -171,6 +251,9 @@ pub fn shooting_system(
                 );
             }
 
+            if downed.is_some() && !gun_stats.gun.map_or(false, Guns::is_sidearm) {
+                continue;
+            }
             gun_stats.timer.tick(time.delta());
             (gun_stats.shoot)(
                 &mut commands,
@@ -189,74 +272,71 @@ pub fn shooting_system(
     }
 }
 
-pub fn player_input_setup(is_controller: bool) -> InputManagerBundle<PlayerActions> {
-    let mut input_map: InputMap<PlayerActions>;
-    if is_controller {
-        input_map = InputMap::new([
-            (GamepadButtonType::RightTrigger2, PlayerActions::Shoot),
-            (GamepadButtonType::LeftTrigger2, PlayerActions::Roll),
-            (GamepadButtonType::North, PlayerActions::Reload),
-            (GamepadButtonType::South, PlayerActions::Pickup),
-            (GamepadButtonType::Start, PlayerActions::DropItem),
-            (GamepadButtonType::Select, PlayerActions::DropWeapon),
-            (GamepadButtonType::DPadRight, PlayerActions::NextWeapon),
-            (GamepadButtonType::DPadLeft, PlayerActions::LastWeapon),
-        ]);
-        input_map
-            .insert(DualAxis::left_stick(), PlayerActions::ControllerMove)
-            .insert(DualAxis::right_stick(), PlayerActions::ControllerLook);
-    } else {
-        input_map = InputMap::new([
-            (KeyCode::Q, PlayerActions::Left),
-            (KeyCode::D, PlayerActions::Right),
-            (KeyCode::Z, PlayerActions::Up),
-            (KeyCode::S, PlayerActions::Down),
-            (KeyCode::Space, PlayerActions::Roll),
-            (KeyCode::R, PlayerActions::Reload),
-            (KeyCode::E, PlayerActions::Pickup),
-            (KeyCode::W, PlayerActions::DropItem),
-            (KeyCode::X, PlayerActions::DropWeapon),
-            (KeyCode::Key1, PlayerActions::LastWeapon),
-            (KeyCode::Key2, PlayerActions::NextWeapon),
-        ]);
-        input_map
-            .insert(MouseButton::Left, PlayerActions::Shoot)
-            .insert(MouseWheelDirection::Up, PlayerActions::NextWeapon)
-            .insert(MouseWheelDirection::Down, PlayerActions::LastWeapon);
-    }
-
+pub fn player_input_setup(
+    gamepad: Option<Gamepad>,
+    profile: &ControlProfile,
+) -> InputManagerBundle<PlayerActions> {
     InputManagerBundle::<PlayerActions> {
         action_state: ActionState::default(),
-        input_map,
+        input_map: profile.input_map(gamepad),
+    }
+}
+
+/// Moves `current` towards `target` by at most `max_delta`
+fn approach(current: Vec2, target: Vec2, max_delta: f32) -> Vec2 {
+    let delta = target - current;
+
+    if delta.length() <= max_delta {
+        target
+    } else {
+        current + delta.normalize() * max_delta
     }
 }
 
 type PlayerEntity<'a> = (
     &'a MoveDirection,
+    &'a CursorPosition,
+    &'a Transform,
     &'a PlayerStats,
     &'a mut Velocity,
     &'a mut AnimationState,
-    Without<RollStats>,
+    (Without<RollStats>, Without<ChoosingClass>),
 );
 
-pub fn move_players(mut query: Query<PlayerEntity>) {
-    for (direction, stats, mut velocity, mut state, _) in &mut query {
+pub fn move_players(
+    time: Res<Time>,
+    surfaces: Res<SurfaceMap>,
+    mut query: Query<PlayerEntity>,
+) {
+    for (direction, cursor, transform, stats, mut velocity, mut state, _) in &mut query {
         if direction.value == Vec2::ZERO {
             *state = AnimationState::new(&PlayerState::Idle);
         } else {
-            *state = match direction.to_angle() {
-                n if (n < 30. + 60. * 0.) => AnimationState::new(&PlayerState::Front),
-                n if (n <= 30. + 60. * 1.) => AnimationState::new(&PlayerState::LeftFront),
-                n if (n < 30. + 60. * 2.) => AnimationState::new(&PlayerState::LeftBack),
-                n if (n < 30. + 60. * 3.) => AnimationState::new(&PlayerState::Back),
-                n if (n < 30. + 60. * 4.) => AnimationState::new(&PlayerState::RightBack),
-                n if (n <= 30. + 60. * 5.) => AnimationState::new(&PlayerState::RightFront),
-                n if (n < 30. + 60. * 6.) => AnimationState::new(&PlayerState::Front),
-                _ => {
-                    panic!("IMPOSSIBLE ANGLE!")
-                }
+            let aim = if cursor.relative == Vec2::ZERO {
+                direction.to_angle()
+            } else {
+                cursor.to_angle()
+            };
+            let facing = PlayerState::facing(aim);
+
+            *state = match angle_difference(aim, direction.to_angle()) {
+                n if n < 60. => AnimationState::new(&facing),
+                n if n <= 120. => AnimationState::new(&facing.strafe()),
+                _ => AnimationState::new(&facing.backpedal()),
             };
         }
-        velocity.linvel = direction.value.clamp_length(0., 1.) * stats.speed;
+        let surface = surfaces.at(transform.translation.truncate());
+        let target = direction.value.clamp_length(0., 1.) * stats.speed * surface.speed();
+        let rate = if target == Vec2::ZERO {
+            stats.deceleration
+        } else {
+            stats.acceleration
+        } * surface.traction();
+
+        velocity.linvel = approach(
+            velocity.linvel,
+            target + surface.push(),
+            rate * time.delta_seconds(),
+        );
     }
 }
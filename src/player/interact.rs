@@ -0,0 +1,176 @@
+use bevy::{math::Vec3Swizzles, prelude::*};
+use leafwing_input_manager::prelude::ActionState;
+
+use super::{
+    class::ChoosingClass,
+    controls::ControlsConfig,
+    direction::CursorPosition,
+    downed::Downed,
+    input::{IsController, PlayerActions},
+    join::PlayerIndex,
+};
+
+pub const INTERACTION_RANGE: f32 = 25. * 1.5;
+/// How much a target behind the player counts as further away than one in the aim direction
+const AIM_PREFERENCE: f32 = 1.;
+const PROMPT_OFFSET: Vec2 = Vec2::new(0., 14.);
+
+/// Something players can act on with the pickup button
+#[derive(Component, Clone, Debug)]
+pub struct Interactable {
+    pub range: f32,
+    /// Beats every lower priority target in range, however close they are
+    pub priority: i32,
+    pub prompt: String,
+}
+
+impl Interactable {
+    pub fn new(prompt: impl Into<String>) -> Self {
+        Interactable {
+            range: INTERACTION_RANGE,
+            priority: 0,
+            prompt: prompt.into(),
+        }
+    }
+
+    pub fn with_range(mut self, range: f32) -> Self {
+        self.range = range;
+        self
+    }
+
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+/// Interactable the player acts on when pressing the pickup button
+#[derive(Component, Default)]
+pub struct InteractionTarget(pub Option<Entity>);
+
+#[derive(Event, Clone, Debug)]
+pub struct InteractEvent {
+    pub player: Entity,
+    pub target: Entity,
+}
+
+/// Button prompt floating above the target of `player`
+#[derive(Component)]
+pub struct InteractionPrompt {
+    pub player: Entity,
+}
+
+pub fn select_interaction_targets(
+    mut players: Query<(
+        Entity,
+        &GlobalTransform,
+        Option<&CursorPosition>,
+        Option<&ChoosingClass>,
+        Option<&Downed>,
+        &mut InteractionTarget,
+    )>,
+    targets: Query<(Entity, &GlobalTransform, &Interactable)>,
+) {
+    for (entity, transform, cursor, choosing, downed, mut target) in &mut players {
+        let position = transform.translation().xy();
+        let aim = cursor.map_or(Vec2::ZERO, |cursor| cursor.relative.normalize_or_zero());
+        let best = targets
+            .iter()
+            // Players picking a class or crawling can't act on anything
+            .filter(|(target, ..)| *target != entity && choosing.is_none() && downed.is_none())
+            .filter_map(|(target, target_transform, interactable)| {
+                let offset = target_transform.translation().xy() - position;
+                let distance = offset.length();
+
+                if distance > interactable.range {
+                    return None;
+                }
+                let alignment = aim.dot(offset.normalize_or_zero());
+                let cost = distance * (1. + AIM_PREFERENCE * (1. - alignment));
+
+                Some((target, interactable.priority, cost))
+            })
+            .max_by(|a, b| a.1.cmp(&b.1).then(b.2.total_cmp(&a.2)))
+            .map(|(target, ..)| target);
+
+        if target.0 != best {
+            target.0 = best;
+        }
+    }
+}
+
+pub fn send_interactions(
+    mut ev_interact: EventWriter<InteractEvent>,
+    players: Query<(Entity, &InteractionTarget, &ActionState<PlayerActions>), Without<Downed>>,
+) {
+    for (player, target, actions) in &players {
+        if let Some(target) = target.0 {
+            if actions.just_pressed(PlayerActions::Pickup) {
+                ev_interact.send(InteractEvent { player, target });
+            }
+        }
+    }
+}
+
+pub fn spawn_interaction_prompts(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    players: Query<Entity, Added<InteractionTarget>>,
+) {
+    for player in &players {
+        commands.spawn((
+            Name::new("Interaction prompt"),
+            Text2dBundle {
+                text: Text::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("fonts/Extended_font.ttf"),
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                    },
+                ),
+                transform: Transform::from_scale(Vec3::splat(0.25)),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            InteractionPrompt { player },
+        ));
+    }
+}
+
+pub fn update_interaction_prompts(
+    mut commands: Commands,
+    controls: Res<ControlsConfig>,
+    players: Query<(&InteractionTarget, &PlayerIndex, Option<&IsController>)>,
+    targets: Query<(&GlobalTransform, &Interactable)>,
+    mut prompts: Query<(
+        Entity,
+        &InteractionPrompt,
+        &mut Text,
+        &mut Transform,
+        &mut Visibility,
+    )>,
+) {
+    for (entity, prompt, mut text, mut transform, mut visibility) in &mut prompts {
+        let Ok((target, index, controller)) = players.get(prompt.player) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+        let Some((target_transform, interactable)) =
+            target.0.and_then(|target| targets.get(target).ok())
+        else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        let button = controls
+            .profile(index.0)
+            .button_label(controller.is_some(), PlayerActions::Pickup);
+        let value = format!("[{button}] {}", interactable.prompt);
+
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+        transform.translation = (target_transform.translation().xy() + PROMPT_OFFSET).extend(100.);
+        *visibility = Visibility::Visible;
+    }
+}
@@ -3,7 +3,7 @@ use leafwing_input_manager::prelude::ActionState;
 use rand::Rng;
 
 use crate::{
-    player::{input::PlayerActions, weapon::{GunEntity, GunStats}, stats::PlayerStats, inventory::pickup::PickupType},
+    player::{input::PlayerActions, weapon::{GunEntity, GunStats}, stats::PlayerStats, inventory::pickup::PickupType, interact::Interactable},
     rendering::{outline::Outline, utils::set_anchor},
 };
 
@@ -11,6 +11,9 @@ use super::{
     DroppedWeaponEvent, PickupWeaponEvent, pickup::{Pickup, Ground},
 };
 
+/// Guns a player can carry at once
+pub const MAX_WEAPONS: usize = 3;
+
 #[derive(Component)]
 pub struct Armory {
     pub content: Vec<Entity>,
@@ -29,6 +32,10 @@ impl Armory {
         self.content.push(entity);
     }
 
+    pub fn is_full(&self) -> bool {
+        self.content.len() >= MAX_WEAPONS
+    }
+
     pub fn next(&mut self) -> usize {
         self.current_weapon_index += 1;
         if self.current_weapon_index > self.content.len() {
@@ -38,21 +45,88 @@ impl Armory {
     }
 }
 
+/// Turns a held gun back into a pickup lying on the ground
+fn gun_to_pickup(
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<Outline>>,
+    ground: Entity,
+    gun: Entity,
+    sprite: &Handle<Image>,
+    stats: &GunStats,
+    pos: Vec3,
+) {
+    let mut rng = rand::thread_rng();
+    let place_rng = rng.gen::<f32>() * 100.;
+
+    commands.entity(gun)
+        .insert(Pickup {
+            anim_offset: place_rng,
+            pickup_type: PickupType::Gun,
+        })
+        .insert(Interactable::new(
+            stats.gun.map_or("Pick up gun".to_string(), |gun| format!("Pick up {gun:?}")),
+        ))
+        .insert(MaterialMesh2dBundle {
+            transform: Transform::default()
+                .with_scale(stats.size.extend(0.))
+                .with_translation(pos.floor()),
+            mesh: meshes
+                .add(Mesh::from(shape::Quad::new(Vec2::splat(2.))))
+                .into(),
+            material: materials.add(Outline {
+                color: Color::WHITE,
+                size: stats.size,
+                thickness: 1.,
+                color_texture: sprite.clone(),
+            }),
+            ..default()
+        })
+        .remove::<Sprite>()
+        .remove::<Handle<Image>>();
+    commands.entity(ground).add_child(gun);
+}
+
 pub fn pickup_weapon(
     mut commands: Commands,
     mut pickup_event: EventReader<PickupWeaponEvent>,
+    mut ev_drop: EventWriter<DroppedWeaponEvent>,
+    mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<Outline>>,
-    mut guns: Query<(&mut Visibility,(Without<Pickup>, With<GunStats>))>,
+    mut guns: Query<(&mut Visibility, &Handle<Image>, &GunStats, Without<Pickup>)>,
+    ground: Query<Entity, With<Ground>>,
     mut pickups: Query<(
         &Handle<Outline>,
         &mut Transform,
         &GunStats,
         Without<PlayerStats>,
     )>,
-    mut players: Query<(&mut Armory, Option<&mut GunEntity>)>,
+    mut players: Query<(&mut Armory, &GlobalTransform, Option<&mut GunEntity>), With<PlayerStats>>,
 ) {
     for PickupWeaponEvent(player_id, pickup_id) in pickup_event.iter() {
-        if let Ok((mut armory, gun_entity, )) = players.get_mut(*player_id) {
+        if let Ok((mut armory, player_pos, gun_entity)) = players.get_mut(*player_id) {
+            let mut dropped = None;
+
+            // Full: the held gun is swapped for the pickup
+            if armory.is_full() {
+                let current_index = armory.current_weapon_index;
+                let gun = armory.content.remove(current_index);
+
+                ev_drop.send(DroppedWeaponEvent(*player_id, gun));
+                if let Ok((_, sprite, stats, _)) = guns.get(gun) {
+                    gun_to_pickup(
+                        &mut commands,
+                        &mut meshes,
+                        &mut materials,
+                        ground.single(),
+                        gun,
+                        sprite,
+                        stats,
+                        player_pos.translation(),
+                    );
+                }
+                dropped = Some(gun);
+            }
             armory.add(*pickup_id);
             armory.current_weapon_index = armory.content.len() - 1;
             commands.entity(*player_id).add_child(*pickup_id);
@@ -72,13 +146,16 @@ pub fn pickup_weapon(
                             ..default()
                         })
                         .remove::<Pickup>()
+                        .remove::<Interactable>()
                         .remove::<Handle<Outline>>()
                         .remove::<Mesh2dHandle>();
                 }
             }
             if let Some(mut holster) = gun_entity {
-                if let Ok((mut visibility, _)) = guns.get_mut(holster.0) {
-                    *visibility = Visibility::Hidden;
+                if Some(holster.0) != dropped {
+                    if let Ok((mut visibility, _, _, _)) = guns.get_mut(holster.0) {
+                        *visibility = Visibility::Hidden;
+                    }
                 }
                 holster.0 = *pickup_id;
             } else {
@@ -98,7 +175,7 @@ pub fn switch_weapon(
 ) {
     for (action, mut armory, mut holster) in &mut query {
         if armory.content.len() <= 1 {
-            return;
+            continue;
         }
         if action.just_pressed(PlayerActions::NextWeapon) {
             armory.current_weapon_index += 1;
@@ -112,7 +189,7 @@ pub fn switch_weapon(
                 armory.current_weapon_index -= 1;
             }
         } else {
-            return;
+            continue;
         }
         if let Some(other_entity) = armory.content.get(armory.current_weapon_index) {
             if let Ok((mut visibility, _)) = guns.get_mut(holster.0) {
@@ -143,41 +220,24 @@ pub fn drop_weapon(
 ) {
     for (entity, action, pos, mut armory, holster_maybe) in &mut query {
         if action.just_pressed(PlayerActions::DropWeapon) {
-            let mut rng = rand::thread_rng();
-            let place_rng = rng.gen::<f32>() * 100.;
-
             if armory.content.is_empty() {
-                return
+                continue;
             }
             let current_index = armory.current_weapon_index;
             let gun = armory.content.remove(current_index);
 
             ev_drop.send(DroppedWeaponEvent(entity, gun));
             let (_, moved_sprite, moved_gun_stats, _) = guns.get(gun).expect("Gun hold innacessible");
-            commands.entity(gun)
-                .insert(Pickup {
-                    anim_offset: place_rng,
-                    pickup_type: PickupType::Gun,
-                })
-                .insert(MaterialMesh2dBundle {
-                    transform: Transform::default()
-                        .with_scale(moved_gun_stats.size.extend(0.))
-                        .with_translation(pos.translation.floor()),
-                    mesh: meshes
-                        .add(Mesh::from(shape::Quad::new(Vec2::splat(2.))))
-                        .into(),
-                    material: materials.add(Outline {
-                        color: Color::WHITE,
-                        size: moved_gun_stats.size,
-                        thickness: 1.,
-                        color_texture: moved_sprite.clone(),
-                    }),
-                    ..default()
-                })
-                .remove::<Sprite>()
-                .remove::<Handle<Image>>();
-            let ground_id = ground.single().0;
-            commands.entity(ground_id).add_child(gun);
+            gun_to_pickup(
+                &mut commands,
+                &mut meshes,
+                &mut materials,
+                ground.single().0,
+                gun,
+                moved_sprite,
+                moved_gun_stats,
+                pos.translation,
+            );
 
             if armory.current_weapon_index as i32 >= armory.content.len() as i32 - 1 {
                 armory.current_weapon_index = 0;
@@ -0,0 +1,263 @@
+use bevy::{math::Vec3Swizzles, prelude::*, sprite::MaterialMesh2dBundle};
+use leafwing_input_manager::prelude::ActionState;
+use serde::Deserialize;
+
+use crate::{
+    player::{
+        class::ChoosingClass,
+        damage::PlayerHealed,
+        downed::Downed,
+        input::PlayerActions,
+        modifiers::{ModifierKind, ModifierSource, StatModifiers, TimedModifiers},
+        roll::RollStats,
+        stats::{PlayerStats, Stat},
+        weapon::{GunEntity, GunStats},
+    },
+    rendering::utils::Zindex,
+};
+
+use super::{
+    inventory_manager::Inventory,
+    item_manager::{ItemId, Items},
+};
+
+const USE_SPEED: f32 = 0.5;
+const SMOKE_FADE: f32 = 1.;
+const SMOKE_OPACITY: f32 = 0.85;
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct Consumable {
+    pub cooldown: f32,
+    /// Seconds the player spends using the item before its effect applies
+    pub use_time: f32,
+    pub effect: ConsumableEffect,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub enum ConsumableEffect {
+    /// Restores health without going over the maximum
+    Heal(f32),
+    /// Reserve ammo given to the held gun
+    RefillAmmo { magazines: i32 },
+    /// Stat modifier lasting `duration` seconds
+    Buff {
+        stat: Stat,
+        kind: ModifierKind,
+        duration: f32,
+    },
+    /// Cloud hiding everything under it
+    Smoke { radius: f32, duration: f32 },
+}
+
+/// Added while a consumable is being used, removed when it applies or gets interrupted
+#[derive(Component)]
+pub struct UsingItem {
+    pub item: ItemId,
+    pub timer: Timer,
+    /// Icon shown above the player during the use
+    pub icon: Entity,
+}
+
+#[derive(Component)]
+pub struct SmokeCloud {
+    pub lifetime: Timer,
+}
+
+fn using_source() -> ModifierSource {
+    ModifierSource::Status("using item".to_string())
+}
+
+pub fn start_using_item(
+    mut commands: Commands,
+    time: Res<Time>,
+    items: Res<Items>,
+    mut players: Query<
+        (
+            Entity,
+            &ActionState<PlayerActions>,
+            &mut Inventory,
+            &mut StatModifiers,
+            Option<&RollStats>,
+            Option<&Downed>,
+            Option<&UsingItem>,
+        ),
+        Without<ChoosingClass>,
+    >,
+) {
+    for (entity, actions, mut inventory, mut modifiers, roll, downed, using) in &mut players {
+        // Ticking alone must not trigger `Changed<Inventory>` filters such as `update_synergies`
+        for cooldown in inventory.bypass_change_detection().cooldowns.values_mut() {
+            cooldown.tick(time.delta());
+        }
+        if !actions.just_pressed(PlayerActions::UseItem)
+            || roll.is_some()
+            || downed.is_some()
+            || using.is_some()
+        {
+            continue;
+        }
+        let Some(stack) = inventory.quick_slot else {
+            continue;
+        };
+        if !inventory.is_ready(stack.item) {
+            continue;
+        }
+        let item = items.get(stack.item);
+        let Some(consumable) = item.definition.consumable else {
+            continue;
+        };
+
+        modifiers.add(
+            using_source(),
+            vec![(Stat::Speed, ModifierKind::Multiplicative(USE_SPEED))],
+        );
+        let icon = commands
+            .spawn((
+                Name::new("Used item"),
+                SpriteBundle {
+                    texture: item.sprite.clone(),
+                    transform: Transform::from_xyz(0., 20., 60.),
+                    ..default()
+                },
+            ))
+            .id();
+        commands.entity(entity).add_child(icon).insert(UsingItem {
+            item: stack.item,
+            timer: Timer::from_seconds(consumable.use_time, TimerMode::Once),
+            icon,
+        });
+    }
+}
+
+type ItemUser<'a> = (
+    Entity,
+    &'a Transform,
+    &'a mut UsingItem,
+    &'a mut Inventory,
+    &'a mut PlayerStats,
+    &'a mut StatModifiers,
+    &'a mut TimedModifiers,
+    Option<&'a GunEntity>,
+    Option<&'a RollStats>,
+    Option<&'a Downed>,
+);
+
+pub fn use_items(
+    mut commands: Commands,
+    time: Res<Time>,
+    items: Res<Items>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut ev_healed: EventWriter<PlayerHealed>,
+    mut players: Query<ItemUser>,
+    mut icons: Query<&mut Transform, Without<PlayerStats>>,
+    mut guns: Query<&mut GunStats, Without<PlayerStats>>,
+) {
+    for (
+        entity,
+        transform,
+        mut using,
+        mut inventory,
+        mut stats,
+        mut modifiers,
+        mut timed,
+        gun,
+        roll,
+        downed,
+    ) in &mut players
+    {
+        // Rolling or going down interrupts the use, the item is kept
+        let interrupted = roll.is_some() || downed.is_some();
+
+        if !interrupted && !using.timer.tick(time.delta()).finished() {
+            if let Ok(mut icon) = icons.get_mut(using.icon) {
+                let progress = using.timer.percent();
+
+                icon.translation.y = 20. + progress * 6.;
+                icon.scale = Vec3::splat(1. + (progress * 12.).sin().abs() * 0.2);
+            }
+            continue;
+        }
+        modifiers.remove_all(&using_source());
+        commands.entity(using.icon).despawn_recursive();
+        commands.entity(entity).remove::<UsingItem>();
+        if interrupted {
+            continue;
+        }
+
+        let item = items.get(using.item);
+        let Some(consumable) = item.definition.consumable else {
+            continue;
+        };
+        if inventory.remove_quick() != Some(using.item) {
+            continue;
+        }
+        inventory.start_cooldown(using.item, consumable.cooldown);
+
+        match consumable.effect {
+            ConsumableEffect::Heal(amount) => {
+                let amount = stats.heal(amount);
+
+                ev_healed.send(PlayerHealed {
+                    target: entity,
+                    amount,
+                });
+            }
+            ConsumableEffect::RefillAmmo { magazines } => {
+                if let Some(mut gun) = gun.and_then(|gun| guns.get_mut(gun.0).ok()) {
+                    if !gun.infinite {
+                        gun.ammo = (gun.ammo + gun.mag_size * magazines).min(gun.max_ammo);
+                    }
+                }
+            }
+            ConsumableEffect::Buff {
+                stat,
+                kind,
+                duration,
+            } => {
+                let source = ModifierSource::Buff(item.definition.name.clone());
+
+                modifiers.remove_all(&source);
+                modifiers.add(source.clone(), vec![(stat, kind)]);
+                timed.add(source, duration);
+            }
+            ConsumableEffect::Smoke { radius, duration } => {
+                commands.spawn((
+                    Name::new("Smoke cloud"),
+                    MaterialMesh2dBundle {
+                        mesh: meshes.add(shape::Circle::new(radius).into()).into(),
+                        material: materials
+                            .add(ColorMaterial::from(Color::GRAY.with_a(SMOKE_OPACITY))),
+                        transform: Transform::from_translation(
+                            transform.translation.xy().extend(0.),
+                        ),
+                        ..default()
+                    },
+                    Zindex(60.),
+                    SmokeCloud {
+                        lifetime: Timer::from_seconds(duration, TimerMode::Once),
+                    },
+                ));
+            }
+        }
+    }
+}
+
+pub fn update_smoke_clouds(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut clouds: Query<(Entity, &Handle<ColorMaterial>, &mut SmokeCloud)>,
+) {
+    for (entity, material, mut cloud) in &mut clouds {
+        if cloud.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        if let Some(material) = materials.get_mut(material) {
+            let fade = (cloud.lifetime.remaining_secs() / SMOKE_FADE).min(1.);
+
+            material.color.set_a(SMOKE_OPACITY * fade);
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use bevy::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::player::{
+    damage::{PlayerDamaged, PlayerHealed, PlayerKilled},
+    modifiers::ModifierKind,
+    stats::{PlayerStats, Stat},
+    weapon::{GunEntity, GunStats},
+};
+
+use super::{
+    inventory_manager::Inventory,
+    item_manager::Items,
+    synergy::{ActiveSynergies, Synergies},
+};
+
+/// Building blocks of the items declared in `items.ron`
+#[derive(Deserialize, Clone, Debug)]
+pub enum ItemEffect {
+    /// Stat modifier applied while the item is held
+    Stat(Stat, ModifierKind),
+    /// Rolled every time the holder damages another player
+    OnHit { chance: f32, effect: TriggerEffect },
+    /// Applied every time the holder kills another player
+    OnKill(TriggerEffect),
+    /// Changes the bullets fired by the holder
+    Bullet(BulletModifier),
+}
+
+pub fn stat_modifiers(effects: &[ItemEffect]) -> Vec<(Stat, ModifierKind)> {
+    effects
+        .iter()
+        .filter_map(|effect| match effect {
+            ItemEffect::Stat(stat, kind) => Some((*stat, *kind)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub enum TriggerEffect {
+    Heal(f32),
+    RestoreShield(f32),
+    /// Bullets put back in the magazine of the held gun
+    RefillMagazine(i32),
+}
+
+impl TriggerEffect {
+    /// Returns the health restored
+    pub fn apply(&self, stats: &mut PlayerStats, gun: Option<Mut<GunStats>>) -> f32 {
+        match *self {
+            TriggerEffect::Heal(amount) => return stats.heal(amount),
+            TriggerEffect::RestoreShield(amount) => {
+                stats.current_shield = (stats.current_shield + amount).min(stats.max_shield);
+            }
+            TriggerEffect::RefillMagazine(amount) => {
+                if let Some(mut gun) = gun {
+                    gun.mag_ammo = (gun.mag_ammo + amount).min(gun.mag_size);
+                }
+            }
+        }
+        0.
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub enum BulletModifier {
+    /// Bullets start slower, `slowdown` times their speed is removed,
+    /// then gain `acceleration` every second
+    Accelerate { slowdown: f32, acceleration: f32 },
+    Damage(f32),
+    Range(f32),
+    CritChance(f32),
+    /// Players a bullet goes through before stopping
+    Piercing(u32),
+}
+
+/// Sum of the bullet modifiers held by a player
+#[derive(Clone, Copy, Debug)]
+pub struct BulletEffects {
+    pub slowdown: f32,
+    pub acceleration: f32,
+    pub damage: f32,
+    pub range: f32,
+    pub crit_chance: f32,
+    pub piercing: u32,
+}
+
+impl Default for BulletEffects {
+    fn default() -> Self {
+        BulletEffects {
+            slowdown: 0.,
+            acceleration: 0.,
+            damage: 1.,
+            range: 1.,
+            crit_chance: 0.,
+            piercing: 0,
+        }
+    }
+}
+
+impl BulletEffects {
+    pub fn add(&mut self, modifier: BulletModifier) {
+        match modifier {
+            BulletModifier::Accelerate {
+                slowdown,
+                acceleration,
+            } => {
+                self.slowdown += slowdown;
+                self.acceleration += acceleration;
+            }
+            BulletModifier::Damage(multiplier) => self.damage *= multiplier,
+            BulletModifier::Range(multiplier) => self.range *= multiplier,
+            BulletModifier::CritChance(chance) => self.crit_chance += chance,
+            BulletModifier::Piercing(count) => self.piercing += count,
+        }
+    }
+}
+
+pub fn trigger_item_effects(
+    items: Res<Items>,
+    synergies: Res<Synergies>,
+    mut ev_damaged: EventReader<PlayerDamaged>,
+    mut ev_killed: EventReader<PlayerKilled>,
+    mut ev_healed: EventWriter<PlayerHealed>,
+    mut players: Query<(
+        &Inventory,
+        &ActiveSynergies,
+        &mut PlayerStats,
+        Option<&GunEntity>,
+    )>,
+    mut guns: Query<&mut GunStats, Without<PlayerStats>>,
+) {
+    let hits = ev_damaged
+        .iter()
+        .filter(|damaged| damaged.source != Some(damaged.target))
+        .filter_map(|damaged| damaged.source.map(|source| (source, false)));
+    let kills = ev_killed
+        .iter()
+        .filter(|killed| killed.killer != Some(killed.target))
+        .filter_map(|killed| killed.killer.map(|killer| (killer, true)));
+    let triggers: Vec<(Entity, bool)> = hits.chain(kills).collect();
+    let mut rng = rand::thread_rng();
+
+    for (holder, kill) in triggers {
+        let Ok((inventory, active, mut stats, gun)) = players.get_mut(holder) else {
+            continue;
+        };
+        // Downed players do not benefit from their items
+        if stats.current_health <= 0. {
+            continue;
+        }
+        let item_effects = inventory
+            .content()
+            .flat_map(|id| items.get(id).definition.effects.iter());
+        let synergy_effects = active
+            .0
+            .iter()
+            .flat_map(|synergy| synergies.0[*synergy].definition.effects.iter());
+
+        for effect in item_effects.chain(synergy_effects) {
+            let triggered = match effect {
+                ItemEffect::OnHit { chance, effect } if !kill => {
+                    rng.gen_bool(chance.clamp(0., 1.) as f64).then_some(effect)
+                }
+                ItemEffect::OnKill(effect) if kill => Some(effect),
+                _ => None,
+            };
+            if let Some(effect) = triggered {
+                let healed =
+                    effect.apply(&mut stats, gun.and_then(|gun| guns.get_mut(gun.0).ok()));
+
+                if healed > 0. {
+                    ev_healed.send(PlayerHealed {
+                        target: holder,
+                        amount: healed,
+                    });
+                }
+            }
+        }
+    }
+}
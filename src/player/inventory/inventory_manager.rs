@@ -1,19 +1,43 @@
-use bevy::{math::Vec3Swizzles, prelude::*};
+use bevy::{math::Vec3Swizzles, prelude::*, utils::HashMap};
 use leafwing_input_manager::prelude::ActionState;
 
 use crate::{
-    player::{input::PlayerActions, inventory::item_manager::Items},
+    player::input::PlayerActions,
     rendering::outline::Outline,
 };
 
-use super::{assets::ItemsAssets, DroppedItemEvent, pickup::Ground};
+use super::{
+    effects::BulletEffects,
+    item_manager::{ItemId, Items},
+    pickup::Ground,
+    DroppedItemEvent,
+};
+
+/// Different items a player can carry at once
+pub const INVENTORY_CAPACITY: usize = 6;
+
+/// Spawns a pickup for `item` on the ground
+pub fn spawn_item_pickup(
+    commands: &mut Commands,
+    items: &Items,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<Outline>>,
+    ground: Entity,
+    item: ItemId,
+    pos: Vec2,
+) {
+    let id_new = commands
+        .spawn(items.to_pickup(item, pos, meshes, materials))
+        .id();
+    commands.entity(ground).add_child(id_new);
+}
 
 pub fn drop_item(
     mut commands: Commands,
     mut ev_drop: EventWriter<DroppedItemEvent>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<Outline>>,
-    sprites: Res<ItemsAssets>,
+    items: Res<Items>,
     ground: Query<(Entity, With<Ground>)>,
     mut query: Query<(
         Entity,
@@ -24,38 +48,186 @@ pub fn drop_item(
 ) {
     for (entity, action, pos, mut inventory) in &mut query {
         if action.just_pressed(PlayerActions::DropItem) {
-            if let Some(item) = inventory.content.pop() {
+            if let Some(item) = inventory.remove_selected() {
                 ev_drop.send(DroppedItemEvent(item, entity));
-                let id_new = commands.spawn(item.to_pickup(
-                    pos.translation.xy(),
+                spawn_item_pickup(
+                    &mut commands,
+                    &items,
                     &mut meshes,
                     &mut materials,
-                    &sprites,
-                )).id();
-                let ground_id = ground.single().0;
-                commands.entity(ground_id).add_child(id_new);
+                    ground.single().0,
+                    item,
+                    pos.translation.xy(),
+                );
             }
         }
     }
 }
 
+pub fn select_item(mut query: Query<(&ActionState<PlayerActions>, &mut Inventory)>) {
+    for (action, mut inventory) in &mut query {
+        let count = inventory.slots.len();
+
+        if count == 0 {
+            continue;
+        }
+        if action.just_pressed(PlayerActions::NextItem) {
+            inventory.selected = (inventory.selected + 1) % count;
+        } else if action.just_pressed(PlayerActions::LastItem) {
+            inventory.selected = (inventory.selected + count - 1) % count;
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ItemStack {
+    pub item: ItemId,
+    pub count: usize,
+}
+
 #[derive(Component)]
 pub struct Inventory {
-    pub content: Vec<Items>,
+    pub slots: Vec<ItemStack>,
+    pub capacity: usize,
+    /// Slot dropped or swapped out by the player
+    pub selected: usize,
+    /// Consumable used with `PlayerActions::UseItem`
+    pub quick_slot: Option<ItemStack>,
+    pub cooldowns: HashMap<ItemId, Timer>,
+    /// Bullet modifiers of the content and its synergies, see `update_synergies`
+    pub bullet: BulletEffects,
 }
 
 impl Inventory {
     pub fn new() -> Inventory {
         Inventory {
-            content: Vec::new(),
+            slots: Vec::new(),
+            capacity: INVENTORY_CAPACITY,
+            selected: 0,
+            quick_slot: None,
+            cooldowns: HashMap::new(),
+            bullet: BulletEffects::default(),
         }
     }
 
-    pub fn add(&mut self, name: Items) {
-        self.content.push(name);
+    /// Every held item, once per copy
+    pub fn content(&self) -> impl Iterator<Item = ItemId> + '_ {
+        self.slots
+            .iter()
+            .flat_map(|stack| std::iter::repeat(stack.item).take(stack.count))
+    }
+
+    pub fn can_add(&self, name: ItemId, items: &Items) -> bool {
+        let definition = &items.get(name).definition;
+
+        if definition.consumable.is_some() {
+            return self.quick_slot.map_or(true, |stack| {
+                stack.item == name && stack.count < definition.max_stack
+            });
+        }
+        match self.slots.iter().find(|stack| stack.item == name) {
+            Some(stack) => stack.count < definition.max_stack,
+            None => self.slots.len() < self.capacity,
+        }
+    }
+
+    /// Returns false when the stack or the inventory is full
+    pub fn add(&mut self, name: ItemId, items: &Items) -> bool {
+        if !self.can_add(name, items) {
+            return false;
+        }
+        if items.get(name).definition.consumable.is_some() {
+            match &mut self.quick_slot {
+                Some(stack) => stack.count += 1,
+                None => {
+                    self.quick_slot = Some(ItemStack {
+                        item: name,
+                        count: 1,
+                    })
+                }
+            }
+            return true;
+        }
+        match self.slots.iter_mut().find(|stack| stack.item == name) {
+            Some(stack) => stack.count += 1,
+            None => self.slots.push(ItemStack {
+                item: name,
+                count: 1,
+            }),
+        }
+        true
+    }
+
+    pub fn selected(&self) -> Option<&ItemStack> {
+        self.slots.get(self.selected)
+    }
+
+    /// Removes a single copy of the selected item
+    pub fn remove_selected(&mut self) -> Option<ItemId> {
+        let stack = self.slots.get_mut(self.selected)?;
+        let item = stack.item;
+
+        stack.count -= 1;
+        if stack.count == 0 {
+            self.slots.remove(self.selected);
+            self.selected = self.selected.min(self.slots.len().saturating_sub(1));
+        }
+        Some(item)
+    }
+
+    /// Replaces the stack `name` would go in, the quick slot for consumables
+    /// and the selected slot otherwise, returns the replaced stack.
+    /// Nothing is swapped when `name` already has a stack, a full stack refuses the pickup.
+    pub fn swap(&mut self, name: ItemId, items: &Items) -> Option<ItemStack> {
+        let stack = if items.get(name).definition.consumable.is_some() {
+            self.quick_slot.as_mut()?
+        } else {
+            if self.slots.iter().any(|stack| stack.item == name) {
+                return None;
+            }
+            self.slots.get_mut(self.selected)?
+        };
+        if stack.item == name {
+            return None;
+        }
+
+        Some(std::mem::replace(
+            stack,
+            ItemStack {
+                item: name,
+                count: 1,
+            },
+        ))
+    }
+
+    /// Removes a single copy of the consumable
+    pub fn remove_quick(&mut self) -> Option<ItemId> {
+        let stack = self.quick_slot.as_mut()?;
+        let item = stack.item;
+
+        stack.count -= 1;
+        if stack.count == 0 {
+            self.quick_slot = None;
+        }
+        Some(item)
+    }
+
+    pub fn is_ready(&self, name: ItemId) -> bool {
+        self.cooldowns
+            .get(&name)
+            .map_or(true, |cooldown| cooldown.finished())
+    }
+
+    pub fn start_cooldown(&mut self, name: ItemId, duration: f32) {
+        self.cooldowns
+            .insert(name, Timer::from_seconds(duration, TimerMode::Once));
     }
 
-    pub fn amount(&self, name: Items) -> usize {
-        self.content.iter().filter(|&n| *n == name).count()
+    pub fn amount(&self, name: ItemId) -> usize {
+        self.slots
+            .iter()
+            .filter(|stack| stack.item == name)
+            .map(|stack| stack.count)
+            .sum()
     }
 }
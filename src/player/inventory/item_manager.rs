@@ -2,65 +2,143 @@ use bevy::prelude::*;
 
 use std::fmt::Debug;
 
-use strum_macros::EnumIter;
+use serde::Deserialize;
 
 use super::{
-    assets::ItemsAssets,
+    consumable::Consumable,
+    effects::{stat_modifiers, BulletEffects, ItemEffect},
     pickup::{PickupBundle, PickupType},
 };
-use crate::rendering::outline::Outline;
+use crate::{
+    data::load_data,
+    player::{modifiers::ModifierKind, stats::Stat},
+    rendering::outline::Outline,
+};
+
+const DEFAULT_MAX_STACK: usize = 5;
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, EnumIter)]
-pub enum Items {
-    HealthApple,
-    Mercury,
+fn default_max_stack() -> usize {
+    DEFAULT_MAX_STACK
 }
 
-impl Items {
-    pub fn to_pickup(
-        self,
-        pos: Vec2,
-        meshes: &mut ResMut<Assets<Mesh>>,
-        materials: &mut ResMut<Assets<Outline>>,
-        sprites: &Res<ItemsAssets>,
-    ) -> PickupBundle {
+/// Index of an item in the loaded `Items`
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct ItemId(pub usize);
+
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone, Copy, Deserialize)]
+pub enum Rarity {
+    #[default]
+    Common,
+    Uncommon,
+    Rare,
+    Legendary,
+}
+
+impl Rarity {
+    /// Relative chance of a loot table landing on this tier
+    pub fn weight(self) -> u32 {
         match self {
-            Items::HealthApple => create_health_apple_pickup(pos, meshes, materials, sprites),
-            Items::Mercury => create_mercury_pickup(pos, meshes, materials, sprites),
+            Rarity::Common => 60,
+            Rarity::Uncommon => 25,
+            Rarity::Rare => 12,
+            Rarity::Legendary => 3,
         }
     }
 }
 
-pub fn create_mercury_pickup(
-    pos: Vec2,
-    meshes: &mut ResMut<Assets<Mesh>>,
-    materials: &mut ResMut<Assets<Outline>>,
-    sprites: &Res<ItemsAssets>,
-) -> PickupBundle {
-    PickupBundle::create(
-        meshes,
-        materials,
-        sprites.mercury.clone(),
-        Vec2::new(16., 16.),
-        "mercury".to_string(),
-        pos,
-        PickupType::Item(Items::Mercury),
-    )
+#[derive(Deserialize, Clone, Debug)]
+pub struct ItemDefinition {
+    pub name: String,
+    pub sprite: String,
+    pub rarity: Rarity,
+    /// Copies a single inventory slot can hold
+    #[serde(default = "default_max_stack")]
+    pub max_stack: usize,
+    #[serde(default)]
+    pub effects: Vec<ItemEffect>,
+    /// Consumables go to the quick-use slot instead of the inventory
+    #[serde(default)]
+    pub consumable: Option<Consumable>,
+}
+
+pub struct Item {
+    pub definition: ItemDefinition,
+    pub sprite: Handle<Image>,
+}
+
+impl Item {
+    pub fn stat_modifiers(&self) -> Vec<(Stat, ModifierKind)> {
+        stat_modifiers(&self.definition.effects)
+    }
 }
 
-pub fn create_health_apple_pickup(
-    pos: Vec2,
-    meshes: &mut ResMut<Assets<Mesh>>,
-    materials: &mut ResMut<Assets<Outline>>,
-    sprites: &Res<ItemsAssets>,
-) -> PickupBundle {
-    PickupBundle::create(
-        meshes,
-        materials,
-        sprites.apple.clone(),
-        Vec2::new(16., 16.),
-        "apple".to_string(),
-        pos,
-        PickupType::Item(Items::HealthApple),
-    )
+#[derive(Resource)]
+pub struct Items(pub Vec<Item>);
+
+impl FromWorld for Items {
+    fn from_world(world: &mut World) -> Self {
+        let definitions: Vec<ItemDefinition> = load_data("items.ron");
+        let asset_server = world.resource::<AssetServer>();
+
+        Items(
+            definitions
+                .into_iter()
+                .map(|definition| Item {
+                    sprite: asset_server.load(definition.sprite.as_str()),
+                    definition,
+                })
+                .collect(),
+        )
+    }
+}
+
+impl Items {
+    pub fn get(&self, id: ItemId) -> &Item {
+        &self.0[id.0]
+    }
+
+    pub fn find(&self, name: &str) -> Option<ItemId> {
+        self.0
+            .iter()
+            .position(|item| item.definition.name == name)
+            .map(ItemId)
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = ItemId> {
+        (0..self.0.len()).map(ItemId)
+    }
+
+    /// Bullet modifiers of every held item, each copy stacks
+    pub fn bullet_effects(&self, content: impl Iterator<Item = ItemId>) -> BulletEffects {
+        let mut effects = BulletEffects::default();
+
+        for id in content {
+            for effect in &self.get(id).definition.effects {
+                if let ItemEffect::Bullet(modifier) = effect {
+                    effects.add(*modifier);
+                }
+            }
+        }
+        effects
+    }
+
+    pub fn to_pickup(
+        &self,
+        id: ItemId,
+        pos: Vec2,
+        meshes: &mut ResMut<Assets<Mesh>>,
+        materials: &mut ResMut<Assets<Outline>>,
+    ) -> PickupBundle {
+        let item = self.get(id);
+
+        PickupBundle::create(
+            meshes,
+            materials,
+            item.sprite.clone(),
+            Vec2::new(16., 16.),
+            item.definition.name.clone(),
+            pos,
+            PickupType::Item(id),
+        )
+    }
 }
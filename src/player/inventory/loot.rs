@@ -0,0 +1,321 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+use rand::{distributions::WeightedIndex, prelude::Distribution, Rng};
+use serde::Deserialize;
+
+use crate::{
+    data::load_data,
+    player::{
+        ability::{Abilities, AbilitySlot},
+        damage::PlayerKilled,
+        team::{TeamMode, TeamRules},
+        wallet::coin_pickup,
+        weapon::GunStats,
+    },
+    rendering::outline::Outline,
+    save::{SaveData, Unlocks},
+};
+
+use super::{
+    armory_manager::Armory,
+    inventory_manager::Inventory,
+    item_manager::{Items, Rarity},
+    magnet::{ammo_pickup, health_pickup},
+    pickup::Ground,
+    weapon_manager::{GunAssets, Guns},
+};
+
+/// Loot table rolled where a player dies
+const DEATH_TABLE: &str = "death";
+/// Distance loot spills from where it was dropped
+const DROP_SCATTER: f32 = 12.;
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+pub enum Loot {
+    /// Name of an item from `items.ron`
+    Item(String),
+    Gun(Guns),
+    /// Name of an ability from `abilities.ron`
+    Ability(String),
+    Coins(u32),
+    Health(f32),
+    /// Magazines of reserve ammo
+    Ammo(i32),
+}
+
+/// Requirement for an entry to be part of a roll
+#[derive(Deserialize, Clone, Debug)]
+pub enum LootCondition {
+    /// No player is carrying this loot already
+    NotHeld,
+    /// Only in co-op games
+    Coop,
+    MinPlayers(usize),
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct LootEntry {
+    pub loot: Loot,
+    /// Weight among the entries of the same rarity
+    pub weight: u32,
+    /// Defaults to the rarity of the item in `items.ron`, or `Common` for anything else
+    #[serde(default)]
+    pub rarity: Option<Rarity>,
+    #[serde(default)]
+    pub conditions: Vec<LootCondition>,
+    /// Base price when sold in a shop
+    #[serde(default)]
+    pub price: u32,
+}
+
+impl LootEntry {
+    pub fn rarity(&self, items: &Items) -> Rarity {
+        self.rarity.unwrap_or_else(|| match &self.loot {
+            Loot::Item(name) => items
+                .find(name)
+                .map_or(Rarity::default(), |item| items.get(item).definition.rarity),
+            _ => Rarity::default(),
+        })
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct LootTable {
+    pub name: String,
+    pub entries: Vec<LootEntry>,
+}
+
+impl LootTable {
+    /// Picks a rarity tier first, then an entry of that tier,
+    /// among the entries passing `allowed`
+    pub fn roll(
+        &self,
+        rng: &mut impl Rng,
+        items: &Items,
+        allowed: impl Fn(&LootEntry) -> bool,
+    ) -> Option<&LootEntry> {
+        let candidates: Vec<&LootEntry> = self
+            .entries
+            .iter()
+            .filter(|&entry| allowed(entry))
+            .collect();
+        let mut tiers: Vec<Rarity> = Vec::new();
+
+        for entry in &candidates {
+            let rarity = entry.rarity(items);
+
+            if !tiers.contains(&rarity) {
+                tiers.push(rarity);
+            }
+        }
+        let tier_weights = WeightedIndex::new(tiers.iter().map(|tier| tier.weight())).ok()?;
+        let tier = tiers[tier_weights.sample(rng)];
+        let entries: Vec<&LootEntry> = candidates
+            .into_iter()
+            .filter(|entry| entry.rarity(items) == tier)
+            .collect();
+        let weights = WeightedIndex::new(entries.iter().map(|entry| entry.weight)).ok()?;
+
+        Some(entries[weights.sample(rng)])
+    }
+}
+
+#[derive(Resource)]
+pub struct LootTables(pub Vec<LootTable>);
+
+impl Default for LootTables {
+    fn default() -> Self {
+        LootTables(load_data("loot_tables.ron"))
+    }
+}
+
+impl LootTables {
+    pub fn get(&self, name: &str) -> Option<&LootTable> {
+        self.0.iter().find(|table| table.name == name)
+    }
+}
+
+/// Seed of the current run, every generated stock derives from it
+#[derive(Resource)]
+pub struct RunSeed(pub u64);
+
+impl Default for RunSeed {
+    fn default() -> Self {
+        RunSeed(rand::random())
+    }
+}
+
+/// What players currently hold, to check the entries conditions against
+#[derive(SystemParam)]
+pub struct LootConditions<'w, 's> {
+    items: Res<'w, Items>,
+    abilities: Res<'w, Abilities>,
+    team_rules: Res<'w, TeamRules>,
+    save: Res<'w, SaveData>,
+    unlocks: Res<'w, Unlocks>,
+    players: Query<
+        'w,
+        's,
+        (
+            &'static Inventory,
+            &'static Armory,
+            Option<&'static AbilitySlot>,
+        ),
+    >,
+    guns: Query<'w, 's, &'static GunStats>,
+}
+
+impl<'w, 's> LootConditions<'w, 's> {
+    pub fn allow(&self, entry: &LootEntry) -> bool {
+        self.unlocks.is_available(&entry.loot, &self.save)
+            && entry.conditions.iter().all(|condition| match condition {
+                LootCondition::NotHeld => !self.is_held(&entry.loot),
+                LootCondition::Coop => self.team_rules.mode == TeamMode::Coop,
+                LootCondition::MinPlayers(count) => self.players.iter().count() >= *count,
+            })
+    }
+
+    fn is_held(&self, loot: &Loot) -> bool {
+        self.players
+            .iter()
+            .any(|(inventory, armory, slot)| match loot {
+                Loot::Item(name) => self
+                    .items
+                    .find(name)
+                    .map_or(false, |item| inventory.amount(item) > 0),
+                Loot::Gun(gun) => armory.content.iter().any(|entity| {
+                    self.guns
+                        .get(*entity)
+                        .map_or(false, |stats| stats.gun == Some(*gun))
+                }),
+                Loot::Ability(name) => slot.map_or(false, |slot| {
+                    self.abilities.find(name) == Some(slot.ability)
+                }),
+                Loot::Coins(_) | Loot::Health(_) | Loot::Ammo(_) => false,
+            })
+    }
+}
+
+/// Everything needed to turn a `Loot` into a pickup lying on the ground
+#[derive(SystemParam)]
+pub struct LootSpawner<'w, 's> {
+    pub commands: Commands<'w, 's>,
+    pub meshes: ResMut<'w, Assets<Mesh>>,
+    pub materials: ResMut<'w, Assets<Outline>>,
+    pub asset_server: Res<'w, AssetServer>,
+    pub items: Res<'w, Items>,
+    pub abilities: Res<'w, Abilities>,
+    pub gun_assets: Res<'w, GunAssets>,
+    pub ground: Query<'w, 's, Entity, With<Ground>>,
+}
+
+impl<'w, 's> LootSpawner<'w, 's> {
+    /// Returns `None` when the loot names something that does not exist
+    pub fn spawn(&mut self, loot: &Loot, pos: Vec2) -> Option<Entity> {
+        let meshes = &mut self.meshes;
+        let materials = &mut self.materials;
+        let pickup = match loot {
+            Loot::Item(name) => {
+                let item = self.items.find(name)?;
+
+                self.commands
+                    .spawn(self.items.to_pickup(item, pos, meshes, materials))
+                    .id()
+            }
+            Loot::Gun(gun) => self
+                .commands
+                .spawn(gun.to_pickup(pos, meshes, materials, &self.gun_assets))
+                .id(),
+            Loot::Ability(name) => {
+                let ability = self.abilities.find(name)?;
+
+                self.commands
+                    .spawn(self.abilities.to_pickup(ability, pos, meshes, materials))
+                    .id()
+            }
+            Loot::Coins(amount) => self
+                .commands
+                .spawn(coin_pickup(
+                    *amount,
+                    pos,
+                    meshes,
+                    materials,
+                    &self.asset_server,
+                ))
+                .id(),
+            Loot::Health(amount) => self
+                .commands
+                .spawn(health_pickup(
+                    *amount,
+                    pos,
+                    meshes,
+                    materials,
+                    &self.asset_server,
+                ))
+                .id(),
+            Loot::Ammo(magazines) => self
+                .commands
+                .spawn(ammo_pickup(
+                    *magazines,
+                    pos,
+                    meshes,
+                    materials,
+                    &self.asset_server,
+                ))
+                .id(),
+        };
+
+        if let Ok(ground) = self.ground.get_single() {
+            self.commands.entity(ground).add_child(pickup);
+        }
+        Some(pickup)
+    }
+}
+
+/// Request to roll a loot table and spill the result on the ground,
+/// sent by chests and deaths
+#[derive(Event, Clone, Debug)]
+pub struct LootDrop {
+    pub table: String,
+    pub rolls: u32,
+    pub position: Vec2,
+}
+
+pub fn drop_death_loot(
+    mut ev_killed: EventReader<PlayerKilled>,
+    mut ev_drop: EventWriter<LootDrop>,
+) {
+    for killed in ev_killed.iter() {
+        ev_drop.send(LootDrop {
+            table: DEATH_TABLE.to_string(),
+            rolls: 1,
+            position: killed.position,
+        });
+    }
+}
+
+pub fn spawn_loot_drops(
+    tables: Res<LootTables>,
+    mut ev_drop: EventReader<LootDrop>,
+    conditions: LootConditions,
+    mut spawner: LootSpawner,
+) {
+    let mut rng = rand::thread_rng();
+
+    for drop in ev_drop.iter() {
+        let Some(table) = tables.get(&drop.table) else {
+            warn!("Unknown loot table {}", drop.table);
+            continue;
+        };
+
+        for _ in 0..drop.rolls {
+            let Some(entry) = table.roll(&mut rng, &spawner.items, |entry| conditions.allow(entry))
+            else {
+                continue;
+            };
+            let offset = Vec2::from_angle(rng.gen_range(0. ..std::f32::consts::TAU))
+                * rng.gen_range(0. ..DROP_SCATTER);
+
+            spawner.spawn(&entry.loot, drop.position + offset);
+        }
+    }
+}
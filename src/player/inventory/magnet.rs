@@ -0,0 +1,141 @@
+use bevy::{math::Vec3Swizzles, prelude::*};
+
+use crate::{
+    player::{
+        class::ChoosingClass,
+        damage::PlayerHealed,
+        downed::Downed,
+        stats::PlayerStats,
+        wallet::Wallet,
+        weapon::{GunEntity, GunStats},
+    },
+    rendering::outline::Outline,
+};
+
+use super::pickup::{MagneticPickupBundle, Pickup, PickupBundle, PickupType};
+
+/// Speed gained every second while a pickup is pulled in
+const MAGNET_ACCELERATION: f32 = 400.;
+/// Distance at which a pulled pickup is collected
+const COLLECT_RANGE: f32 = 6.;
+
+#[derive(Component, Default)]
+pub struct Magnetic {
+    pub speed: f32,
+}
+
+pub fn health_pickup(
+    amount: f32,
+    pos: Vec2,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<Outline>>,
+    asset_server: &AssetServer,
+) -> MagneticPickupBundle {
+    PickupBundle::create(
+        meshes,
+        materials,
+        asset_server.load("items/Item__67.png"),
+        Vec2::new(16., 16.),
+        format!("{amount} health"),
+        pos,
+        PickupType::Health(amount),
+    )
+    .magnetic()
+}
+
+pub fn ammo_pickup(
+    magazines: i32,
+    pos: Vec2,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<Outline>>,
+    asset_server: &AssetServer,
+) -> MagneticPickupBundle {
+    PickupBundle::create(
+        meshes,
+        materials,
+        asset_server.load("guns/bullet.png"),
+        Vec2::new(6., 6.),
+        format!("{magazines} magazines"),
+        pos,
+        PickupType::Ammo(magazines),
+    )
+    .magnetic()
+}
+
+/// Whether the player gets anything out of the pickup
+fn is_useful(pickup_type: &PickupType, stats: &PlayerStats, gun: Option<&GunStats>) -> bool {
+    match pickup_type {
+        PickupType::Health(_) => stats.current_health < stats.max_health,
+        PickupType::Ammo(_) => gun.map_or(false, |gun| !gun.infinite && gun.ammo < gun.max_ammo),
+        _ => true,
+    }
+}
+
+/// Pulls magnetic pickups toward the closest player whose magnet radius reaches them
+pub fn attract_pickups(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut ev_healed: EventWriter<PlayerHealed>,
+    mut pickups: Query<(Entity, &mut Transform, &Pickup, &mut Magnetic), Without<PlayerStats>>,
+    mut players: Query<
+        (
+            Entity,
+            &Transform,
+            &mut PlayerStats,
+            &mut Wallet,
+            Option<&GunEntity>,
+        ),
+        (Without<ChoosingClass>, Without<Downed>),
+    >,
+    mut guns: Query<&mut GunStats, Without<PlayerStats>>,
+) {
+    for (entity, mut transform, pickup, mut magnet) in &mut pickups {
+        let position = transform.translation.xy();
+        let closest = players
+            .iter()
+            .filter(|(_, player_pos, stats, _, gun)| {
+                let gun = gun.and_then(|gun| guns.get(gun.0).ok());
+
+                is_useful(&pickup.pickup_type, stats, gun)
+                    && player_pos.translation.xy().distance(position) < stats.magnet_radius
+            })
+            .map(|(player, player_pos, ..)| (player, player_pos.translation.xy()))
+            .min_by(|a, b| a.1.distance(position).total_cmp(&b.1.distance(position)));
+
+        let Some((player, player_position)) = closest else {
+            magnet.speed = 0.;
+            continue;
+        };
+        if player_position.distance(position) > COLLECT_RANGE {
+            magnet.speed += MAGNET_ACCELERATION * time.delta_seconds();
+            let step = (player_position - position).normalize_or_zero()
+                * (magnet.speed * time.delta_seconds()).min(player_position.distance(position));
+            transform.translation += step.extend(0.);
+            continue;
+        }
+
+        let Ok((_, _, mut stats, mut wallet, gun)) = players.get_mut(player) else {
+            continue;
+        };
+        match pickup.pickup_type {
+            PickupType::Coins(amount) => wallet.coins += amount,
+            PickupType::Health(amount) => {
+                let amount = stats.heal(amount);
+
+                ev_healed.send(PlayerHealed {
+                    target: player,
+                    amount,
+                });
+            }
+            PickupType::Ammo(magazines) => {
+                if let Some(mut gun) = gun.and_then(|gun| guns.get_mut(gun.0).ok()) {
+                    if !gun.infinite {
+                        gun.ammo = (gun.ammo + gun.mag_size * magazines).min(gun.max_ammo);
+                    }
+                }
+            }
+            _ => {}
+        }
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -1,51 +1,74 @@
 pub mod armory_manager;
-pub mod assets;
+pub mod consumable;
+pub mod effects;
 pub mod inventory_manager;
 pub mod item_manager;
+pub mod loot;
+pub mod magnet;
 pub mod pickup;
 pub mod stats;
+pub mod synergy;
 pub mod weapon_manager;
 
 use bevy::prelude::*;
-use bevy_asset_loader::prelude::*;
 
 use self::{
     armory_manager::{pickup_weapon, drop_weapon, switch_weapon},
-    assets::ItemsAssets,
-    inventory_manager::drop_item,
-    item_manager::Items,
-    pickup::{spawn_items, update_pickup},
+    consumable::{start_using_item, update_smoke_clouds, use_items},
+    inventory_manager::{drop_item, select_item},
+    item_manager::{ItemId, Items},
+    loot::{drop_death_loot, spawn_loot_drops, LootDrop, LootTables, RunSeed},
+    magnet::attract_pickups,
+    pickup::{spawn_ground, update_pickup},
     stats::{drop_events, pickup_events},
+    synergy::{update_synergies, Synergies, SynergyActivated},
 };
 
 #[derive(Event)]
-pub struct PickupItemEvent(Items, Entity);
+pub struct PickupItemEvent(pub ItemId, pub Entity);
 
 #[derive(Event)]
-pub struct DroppedItemEvent(Items, Entity);
+pub struct DroppedItemEvent(pub ItemId, pub Entity);
 
 #[derive(Event)]
-pub struct PickupWeaponEvent(Entity, Entity); // player then pickup entity
+pub struct PickupWeaponEvent(pub Entity, pub Entity); // player then pickup entity
 
 #[derive(Event)]
-pub struct DroppedWeaponEvent(Entity, Entity);
+pub struct DroppedWeaponEvent(pub Entity, pub Entity);
 
 pub struct ItemsPlugin;
 
 impl Plugin for ItemsPlugin {
     fn build(&self, app: &mut App) {
-        app.init_collection::<ItemsAssets>()
+        app.init_resource::<Items>()
+            .init_resource::<Synergies>()
+            .init_resource::<LootTables>()
+            .init_resource::<RunSeed>()
+            .add_event::<SynergyActivated>()
+            .add_event::<LootDrop>()
             .add_event::<PickupItemEvent>()
             .add_event::<DroppedItemEvent>()
             .add_event::<PickupWeaponEvent>()
             .add_event::<DroppedWeaponEvent>()
-            .add_systems(Startup, spawn_items)
+            .add_systems(Startup, spawn_ground)
+            .add_systems(Update, (drop_death_loot, spawn_loot_drops).chain())
             .add_systems(Update, update_pickup)
-            .add_systems(Update, drop_item)
+            .add_systems(Update, attract_pickups.after(update_pickup))
+            .add_systems(Update, (select_item, drop_item).chain())
             .add_systems(Update, pickup_events)
+            .add_systems(Update, (start_using_item, use_items).chain())
+            .add_systems(Update, update_smoke_clouds)
             .add_systems(Update, pickup_weapon)
             .add_systems(Update, drop_weapon)
             .add_systems(Update, switch_weapon)
-            .add_systems(Update, drop_events);
+            .add_systems(Update, drop_events)
+            .add_systems(
+                Update,
+                update_synergies
+                    .after(update_pickup)
+                    .after(drop_item)
+                    .after(pickup_weapon)
+                    .after(drop_weapon),
+            );
     }
 }
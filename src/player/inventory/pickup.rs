@@ -1,34 +1,39 @@
-use std::f32::INFINITY;
-
 use bevy::{math::Vec3Swizzles, prelude::*, sprite::MaterialMesh2dBundle};
-use leafwing_input_manager::prelude::ActionState;
 use rand::Rng;
-use strum::IntoEnumIterator;
 
 use crate::{
     player::{
-        input::PlayerActions,
-        inventory::{inventory_manager::Inventory, item_manager::Items},
-        stats::PlayerStats, weapon::GunStats,
+        ability::{Abilities, AbilitySlot},
+        class::ChoosingClass,
+        interact::{InteractEvent, Interactable, InteractionTarget},
+        inventory::{
+            inventory_manager::{spawn_item_pickup, Inventory},
+            item_manager::{ItemId, Items},
+        },
+        stats::PlayerStats, team::Team, wallet::Wallet, weapon::GunStats,
     },
     rendering::{outline::Outline, utils::Angle},
     rendering::utils::Zindex,
 };
 
 use super::{
-    assets::ItemsAssets,
-    weapon_manager::{GunAssets, Guns},
-    PickupItemEvent, PickupWeaponEvent,
+    magnet::Magnetic,
+    DroppedItemEvent, PickupItemEvent, PickupWeaponEvent,
 };
 
-const PICKUP_RANGE: f32 = 25. * 1.5;
-
 pub fn update_pickup(
     time: Res<Time>,
+    mut ev_interact: EventReader<InteractEvent>,
     mut ev_pickup_i: EventWriter<PickupItemEvent>,
     mut ev_pickup_w: EventWriter<PickupWeaponEvent>,
+    mut ev_drop_i: EventWriter<DroppedItemEvent>,
     mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<Outline>>,
+    abilities: Res<Abilities>,
+    items: Res<Items>,
+    ground: Query<Entity, With<Ground>>,
+    prices: Query<&Price>,
     mut pickups: Query<(
         Entity,
         &Handle<Outline>,
@@ -38,11 +43,11 @@ pub fn update_pickup(
         Without<PlayerStats>,
     )>,
     mut players: Query<(
-        Entity,
-        &mut Transform,
+        &InteractionTarget,
         &mut Inventory,
-        &ActionState<PlayerActions>,
-        With<PlayerStats>,
+        &mut Wallet,
+        Option<&Team>,
+        (With<PlayerStats>, Without<ChoosingClass>),
     )>,
 ) {
     for (_, outline, mut pos, pickup, mut zindex, _) in &mut pickups {
@@ -55,37 +60,80 @@ pub fn update_pickup(
         }
     }
 
-    for (entity, player_pos, mut inventory, actions, _) in &mut players {
-        let mut nearest: Option<Entity> = None;
-        let mut distance: f32 = INFINITY;
+    for (target, _, wallet, team, _) in &players {
+        let Some(target) = target.0 else {
+            continue;
+        };
+        let Ok((_, outline, ..)) = pickups.get(target) else {
+            continue;
+        };
+        let affordable = prices
+            .get(target)
+            .map_or(true, |price| wallet.coins >= price.amount);
 
-        for (entity, _, pos, _, _, _) in &mut pickups {
-            let current_distance = pos.translation.xy().distance(player_pos.translation.xy());
-
-            if current_distance < distance && current_distance < PICKUP_RANGE {
-                distance = current_distance;
-                nearest = Some(entity);
-            }
+        if let Some(material) = materials.get_mut(outline) {
+            material.color = if affordable {
+                team.map_or(Color::WHITE, Team::color)
+            } else {
+                Color::RED
+            };
         }
+    }
 
-        if let Some(valid_pickup) = nearest {
-            if let Ok((_, outline, _, pickup, _, _)) = pickups.get(valid_pickup) {
-                if let Some(material) = materials.get_mut(outline) {
-                    material.color = Color::WHITE;
-                }
-                if actions.just_pressed(PlayerActions::Pickup) {
-                    match &pickup.pickup_type {
-                        PickupType::Gun => {
-                            ev_pickup_w.send(PickupWeaponEvent(entity, valid_pickup));
-                        }
-                        PickupType::Item(item) => {
-                            ev_pickup_i.send(PickupItemEvent(*item, entity));
-                            inventory.add(*item);
-                            commands.entity(valid_pickup).despawn_recursive();
-                        }
+    for InteractEvent { player, target } in ev_interact.iter() {
+        let Ok((_, _, pos, pickup, _, _)) = pickups.get(*target) else {
+            continue;
+        };
+        let Ok((_, mut inventory, mut wallet, _, _)) = players.get_mut(*player) else {
+            continue;
+        };
+        let price = prices.get(*target).ok();
+
+        if price.map_or(false, |price| wallet.coins < price.amount) {
+            continue;
+        }
+        match &pickup.pickup_type {
+            PickupType::Gun => {
+                ev_pickup_w.send(PickupWeaponEvent(*player, *target));
+            }
+            PickupType::Item(item) => {
+                if !inventory.add(*item, &items) {
+                    // Full inventory: the stack it would go in is swapped for the pickup,
+                    // a full stack of the same item refuses it
+                    let Some(stack) = inventory.swap(*item, &items) else {
+                        continue;
+                    };
+                    for _ in 0..stack.count {
+                        ev_drop_i.send(DroppedItemEvent(stack.item, *player));
+                        spawn_item_pickup(
+                            &mut commands,
+                            &items,
+                            &mut meshes,
+                            &mut materials,
+                            ground.single(),
+                            stack.item,
+                            pos.translation.xy(),
+                        );
                     }
                 }
+                ev_pickup_i.send(PickupItemEvent(*item, *player));
             }
+            PickupType::Ability(ability) => {
+                commands
+                    .entity(*player)
+                    .insert(AbilitySlot::new(*ability, &abilities));
+            }
+            // Collected on contact by `attract_pickups`
+            PickupType::Coins(_) | PickupType::Health(_) | PickupType::Ammo(_) => continue,
+        }
+        if let Some(price) = price {
+            wallet.spend(price.amount);
+            commands.entity(price.label).despawn_recursive();
+            commands.entity(*target).remove::<Price>();
+        }
+        // Guns stay alive, they are moved in the armory
+        if !matches!(pickup.pickup_type, PickupType::Gun) {
+            commands.entity(*target).despawn_recursive();
         }
     }
 }
@@ -93,47 +141,28 @@ pub fn update_pickup(
 #[derive(Component)]
 pub struct Ground;
 
-pub fn spawn_items(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<Outline>>,
-    assets: Res<ItemsAssets>,
-    gun_assets: Res<GunAssets>,
-) {
-    commands.spawn(Ground)
-        .insert(SpatialBundle::default())
-        .insert(Name::new("Ground"))
-        .with_children(|parent| {
-        let len = Items::iter().count();
-        for (x, item) in Items::iter().enumerate() {
-            for _ in 0..10 {
-                parent.spawn(item.to_pickup(
-                    Vec2::new(-(len as f32 * 30.) / 2. + x as f32 * 30. + 15., 80.),
-                    &mut meshes,
-                    &mut materials,
-                    &assets,
-                ));
-            }
-        }
-
-        let len: usize = Guns::iter().count();
-        for (x, gun) in Guns::iter().enumerate() {
-            for _ in 0..10 {
-                parent.spawn(gun.to_pickup(
-                    Vec2::new(-(len as f32 * 30.) / 2. + x as f32 * 30. + 15., 110.),
-                    &mut meshes,
-                    &mut materials,
-                    &gun_assets,
-                ));
-            }
-        }
-    }
-    );
+/// Parent of everything lying on the floor of the level
+pub fn spawn_ground(mut commands: Commands) {
+    commands.spawn((Ground, SpatialBundle::default(), Name::new("Ground")));
 }
 
 pub enum PickupType {
     Gun,
-    Item(Items),
+    Item(ItemId),
+    /// Index in the loaded `Abilities`
+    Ability(usize),
+    Coins(u32),
+    Health(f32),
+    /// Magazines of reserve ammo for the held gun
+    Ammo(i32),
+}
+
+/// Coins deducted from the wallet when the pickup is taken
+#[derive(Component)]
+pub struct Price {
+    pub amount: u32,
+    /// Text showing the price, removed once bought
+    pub label: Entity,
 }
 
 #[derive(Component)]
@@ -150,6 +179,7 @@ pub struct GunPickupBundle {
     pub angle: Angle,
     pub stats: GunStats,
     pub pickup: Pickup,
+    pub interactable: Interactable,
 }
 
 impl GunPickupBundle {
@@ -165,6 +195,7 @@ impl GunPickupBundle {
         let place_rng = rng.gen::<f32>() * 100.;
 
         GunPickupBundle {
+            interactable: Interactable::new(format!("Pick up {name}")),
             name: bevy::core::Name::new(name),
             material: MaterialMesh2dBundle {
                 transform: Transform::default()
@@ -198,6 +229,17 @@ pub struct PickupBundle {
     pub material: MaterialMesh2dBundle<Outline>,
     pub zindex: Zindex,
     pub pickup: Pickup,
+    pub interactable: Interactable,
+}
+
+/// Pickup collected on contact instead of with the pickup button
+#[derive(Bundle)]
+pub struct MagneticPickupBundle {
+    pub name: bevy::core::Name,
+    pub material: MaterialMesh2dBundle<Outline>,
+    pub zindex: Zindex,
+    pub pickup: Pickup,
+    pub magnetic: Magnetic,
 }
 
 impl PickupBundle {
@@ -214,6 +256,7 @@ impl PickupBundle {
         let place_rng = rng.gen::<f32>() * 100.;
 
         PickupBundle {
+            interactable: Interactable::new(format!("Pick up {name}")),
             name: bevy::core::Name::new(name),
             material: MaterialMesh2dBundle {
                 transform: Transform::default()
@@ -237,4 +280,14 @@ impl PickupBundle {
             },
         }
     }
+
+    pub fn magnetic(self) -> MagneticPickupBundle {
+        MagneticPickupBundle {
+            name: self.name,
+            material: self.material,
+            zindex: self.zindex,
+            pickup: self.pickup,
+            magnetic: Magnetic::default(),
+        }
+    }
 }
@@ -1,37 +1,29 @@
 use bevy::prelude::*;
 
-use crate::player::stats::PlayerStats;
+use crate::player::modifiers::{ModifierSource, StatModifiers};
 
 use super::item_manager::Items;
 use super::{DroppedItemEvent, PickupItemEvent};
 
 pub fn pickup_events(
+    items: Res<Items>,
     mut pickup: EventReader<PickupItemEvent>,
-    mut players: Query<&mut PlayerStats>,
+    mut players: Query<&mut StatModifiers>,
 ) {
     for PickupItemEvent(item, player) in pickup.iter() {
-        if let Ok(mut stats) = players.get_mut(*player) {
-            match item {
-                Items::HealthApple => {
-                    stats.max_health += 50.;
-                    stats.current_health += 50.
-                }
-                _ => {}
-            }
+        if let Ok(mut modifiers) = players.get_mut(*player) {
+            modifiers.add(ModifierSource::Item(*item), items.get(*item).stat_modifiers());
         }
     }
 }
 
-pub fn drop_events(mut drop: EventReader<DroppedItemEvent>, mut players: Query<&mut PlayerStats>) {
+pub fn drop_events(
+    mut drop: EventReader<DroppedItemEvent>,
+    mut players: Query<&mut StatModifiers>,
+) {
     for DroppedItemEvent(item, player) in drop.iter() {
-        if let Ok(mut stats) = players.get_mut(*player) {
-            match item {
-                Items::HealthApple => {
-                    stats.max_health -= 50.;
-                    stats.current_health -= 50.
-                }
-                _ => {}
-            }
+        if let Ok(mut modifiers) = players.get_mut(*player) {
+            modifiers.remove(&ModifierSource::Item(*item));
         }
     }
 }
@@ -0,0 +1,142 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    data::load_data,
+    player::{
+        modifiers::{ModifierSource, StatModifiers},
+        weapon::GunStats,
+    },
+};
+
+use super::{
+    armory_manager::Armory,
+    effects::{stat_modifiers, ItemEffect},
+    inventory_manager::Inventory,
+    item_manager::{ItemId, Items},
+    weapon_manager::Guns,
+};
+
+/// Bonus unlocked while a player holds every listed item, and the gun if there is one
+#[derive(Deserialize, Clone, Debug)]
+pub struct SynergyDefinition {
+    pub name: String,
+    pub items: Vec<String>,
+    #[serde(default)]
+    pub gun: Option<Guns>,
+    pub effects: Vec<ItemEffect>,
+}
+
+pub struct Synergy {
+    pub definition: SynergyDefinition,
+    pub items: Vec<ItemId>,
+}
+
+impl Synergy {
+    pub fn is_active(&self, inventory: &Inventory, guns: &[Guns]) -> bool {
+        self.items.iter().all(|item| inventory.amount(*item) > 0)
+            && self.definition.gun.map_or(true, |gun| guns.contains(&gun))
+    }
+
+    fn source(&self) -> ModifierSource {
+        ModifierSource::Synergy(self.definition.name.clone())
+    }
+}
+
+#[derive(Resource)]
+pub struct Synergies(pub Vec<Synergy>);
+
+impl FromWorld for Synergies {
+    fn from_world(world: &mut World) -> Self {
+        let definitions: Vec<SynergyDefinition> = load_data("synergies.ron");
+        let items = world.resource::<Items>();
+
+        Synergies(
+            definitions
+                .into_iter()
+                .map(|definition| Synergy {
+                    items: definition
+                        .items
+                        .iter()
+                        .map(|name| {
+                            items.find(name).unwrap_or_else(|| {
+                                panic!("Unknown item {name} in synergy {}", definition.name)
+                            })
+                        })
+                        .collect(),
+                    definition,
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Indices in `Synergies` of the bonuses a player currently has
+#[derive(Component, Default)]
+pub struct ActiveSynergies(pub Vec<usize>);
+
+#[derive(Event)]
+pub struct SynergyActivated {
+    pub player: Entity,
+    pub synergy: usize,
+}
+
+/// Checks the synergies again whenever items or guns change hands,
+/// and refreshes the bullet modifiers they share with items
+pub fn update_synergies(
+    items: Res<Items>,
+    synergies: Res<Synergies>,
+    mut ev_activated: EventWriter<SynergyActivated>,
+    mut players: Query<
+        (
+            Entity,
+            &mut Inventory,
+            &Armory,
+            &mut ActiveSynergies,
+            &mut StatModifiers,
+        ),
+        Or<(Changed<Inventory>, Changed<Armory>)>,
+    >,
+    guns: Query<&GunStats>,
+) {
+    for (entity, mut inventory, armory, mut active, mut modifiers) in &mut players {
+        let held_guns: Vec<Guns> = armory
+            .content
+            .iter()
+            .filter_map(|gun| guns.get(*gun).ok().and_then(|stats| stats.gun))
+            .collect();
+
+        for (index, synergy) in synergies.0.iter().enumerate() {
+            let was_active = active.0.contains(&index);
+
+            match (was_active, synergy.is_active(&inventory, &held_guns)) {
+                (false, true) => {
+                    active.0.push(index);
+                    modifiers.add(synergy.source(), stat_modifiers(&synergy.definition.effects));
+                    ev_activated.send(SynergyActivated {
+                        player: entity,
+                        synergy: index,
+                    });
+                }
+                (true, false) => {
+                    active.0.retain(|active| *active != index);
+                    modifiers.remove_all(&synergy.source());
+                }
+                _ => {}
+            }
+        }
+
+        let mut bullet = items.bullet_effects(inventory.content());
+        for effect in active
+            .0
+            .iter()
+            .flat_map(|synergy| synergies.0[*synergy].definition.effects.iter())
+        {
+            if let ItemEffect::Bullet(modifier) = effect {
+                bullet.add(*modifier);
+            }
+        }
+        // Not a change of content, it must not trigger this system again
+        inventory.bypass_change_detection().bullet = bullet;
+    }
+}
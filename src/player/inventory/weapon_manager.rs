@@ -1,5 +1,6 @@
 use bevy::prelude::*;
 use bevy_asset_loader::prelude::*;
+use serde::{Deserialize, Serialize};
 use strum_macros::EnumIter;
 
 use super::pickup::GunPickupBundle;
@@ -29,7 +30,7 @@ pub struct GunAssets {
     pub flame_thrower: Handle<Image>,
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, EnumIter, Reflect)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, EnumIter, Reflect, Serialize, Deserialize)]
 pub enum Guns {
     Revolver,
     Shotgun,
@@ -42,6 +43,11 @@ pub enum Guns {
 }
 
 impl Guns {
+    /// Light guns players can still use while downed
+    pub fn is_sidearm(self) -> bool {
+        matches!(self, Guns::Revolver | Guns::SemiAuto)
+    }
+
     pub fn to_pickup(
         self,
         pos: Vec2,
@@ -0,0 +1,93 @@
+use std::time::Duration;
+
+use bevy::{input::gamepad::GamepadConnectionEvent, prelude::*, window::PrimaryWindow};
+use leafwing_input_manager::prelude::*;
+
+use crate::mouse::Mouse;
+
+use super::{
+    class::CharacterClasses,
+    controls::ControlsConfig,
+    input::{IsController, PlayerActions},
+    setup::PlayerBundle,
+};
+
+pub const MAX_PLAYERS: usize = 4;
+const LEAVE_HOLD_DURATION: Duration = Duration::from_millis(1500);
+
+#[derive(Component, Reflect, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PlayerIndex(pub usize);
+
+pub fn join_players(
+    mut commands: Commands,
+    keyboard: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    gamepads: Res<Gamepads>,
+    window: Query<Entity, With<PrimaryWindow>>,
+    classes: Res<CharacterClasses>,
+    controls: Res<ControlsConfig>,
+    players: Query<(&PlayerIndex, Option<&IsController>)>,
+) {
+    let mut joining: Vec<Option<Gamepad>> = Vec::new();
+
+    if keyboard.just_pressed(KeyCode::Return)
+        && players.iter().all(|(_, controller)| controller.is_some())
+    {
+        joining.push(None);
+    }
+    for gamepad in gamepads.iter() {
+        let already_playing = players
+            .iter()
+            .any(|(_, controller)| controller.map_or(false, |c| c.0 == gamepad));
+
+        if !already_playing
+            && buttons.just_pressed(GamepadButton::new(gamepad, GamepadButtonType::Start))
+        {
+            joining.push(Some(gamepad));
+        }
+    }
+
+    let mut taken: Vec<usize> = players.iter().map(|(index, _)| index.0).collect();
+    for gamepad in joining {
+        let Some(index) = (0..MAX_PLAYERS).find(|index| !taken.contains(index)) else {
+            return;
+        };
+        taken.push(index);
+        PlayerBundle::setup(
+            &mut commands,
+            &window,
+            gamepad,
+            index,
+            &classes.0[0].assets,
+            &controls,
+        );
+    }
+}
+
+pub fn leave_players(
+    mut commands: Commands,
+    mut connections: EventReader<GamepadConnectionEvent>,
+    window: Query<Entity, With<PrimaryWindow>>,
+    players: Query<(Entity, &ActionState<PlayerActions>, Option<&IsController>)>,
+) {
+    let disconnected: Vec<Gamepad> = connections
+        .iter()
+        .filter(|event| event.disconnected())
+        .map(|event| event.gamepad)
+        .collect();
+
+    for (entity, actions, controller) in &players {
+        let unplugged = controller.map_or(false, |c| disconnected.contains(&c.0));
+        let held_leave = actions.pressed(PlayerActions::Leave)
+            && actions.current_duration(PlayerActions::Leave) >= LEAVE_HOLD_DURATION;
+
+        if unplugged || held_leave {
+            if controller.is_none() {
+                commands
+                    .entity(window.single())
+                    .remove::<ActionStateDriver<Mouse>>();
+            }
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -1,16 +1,26 @@
+pub mod ability;
 pub mod assets;
 pub mod bullets;
+pub mod class;
+pub mod controls;
+pub mod damage;
 pub mod direction;
+pub mod downed;
 pub mod guns;
 pub mod input;
+pub mod interact;
 pub mod inventory;
+pub mod join;
+pub mod modifiers;
 pub mod reload;
 pub mod roll;
 pub mod setup;
 pub mod stats;
+pub mod team;
+pub mod wallet;
 pub mod weapon;
 
-use bevy::{prelude::*, window::PrimaryWindow};
+use bevy::{prelude::*, transform::TransformSystem, window::PrimaryWindow};
 
 use bevy_asset_loader::prelude::*;
 
@@ -20,7 +30,7 @@ use stats::PlayerStats;
 
 use crate::mouse::update_cursor_state_from_window;
 
-use self::assets::PlayerAssets;
+use self::class::CharacterClasses;
 use self::inventory::weapon_manager::GunAssets;
 use self::weapon::GunEntity;
 pub struct PlayerPlugin;
@@ -30,11 +40,36 @@ impl Plugin for PlayerPlugin {
         app.register_type::<PlayerStats>()
             .register_type::<PlayerState>()
             .register_type::<GunEntity>()
-            .init_collection::<PlayerAssets>()
+            .register_type::<join::PlayerIndex>()
+            .register_type::<team::Team>()
+            .init_resource::<team::TeamRules>()
+            .init_resource::<CharacterClasses>()
+            .init_resource::<ability::Abilities>()
+            .add_event::<ability::AmmoPulseEvent>()
+            .init_resource::<damage::Scoreboard>()
+            .add_event::<damage::DamageEvent>()
+            .add_event::<damage::PlayerDamaged>()
+            .add_event::<damage::PlayerHealed>()
+            .add_event::<damage::PlayerKilled>()
+            .add_event::<downed::PlayerDowned>()
+            .add_event::<downed::PlayerRevived>()
+            .add_event::<interact::InteractEvent>()
             .init_collection::<GunAssets>()
+            .insert_resource(controls::ControlsConfig::load())
             .add_plugins(InputManagerPlugin::<input::PlayerActions>::default())
             .add_plugins(inventory::ItemsPlugin)
             .add_systems(Startup, setup_players)
+            .add_systems(Update, (join::join_players, join::leave_players))
+            .add_systems(Update, (team::switch_team_rules, team::assign_teams).chain())
+            .add_systems(
+                Update,
+                (
+                    controls::cycle_keyboard_layout,
+                    controls::apply_controls,
+                    controls::save_controls,
+                )
+                    .chain(),
+            )
             .add_systems(First, direction::calculate_players_cursors)
             .add_systems(First, direction::calculate_players_move_direction)
             .add_systems(Update, reload::start_reload)
@@ -44,6 +79,8 @@ impl Plugin for PlayerPlugin {
                 input::move_players.after(update_cursor_state_from_window),
             )
             .add_systems(Update, roll::rolling.after(update_cursor_state_from_window))
+            .add_systems(Update, (roll::roll_cooldown, roll::regenerate_stamina))
+            .add_systems(Update, stats::regenerate_shield)
             .add_systems(
                 Update,
                 input::shooting_system.after(update_cursor_state_from_window),
@@ -57,6 +94,59 @@ impl Plugin for PlayerPlugin {
                 Update,
                 bullets::detect_collision_bullets.after(update_cursor_state_from_window),
             )
+            .add_systems(
+                Update,
+                (
+                    damage::apply_damage,
+                    downed::down_players,
+                    downed::bleed_out,
+                    damage::update_scoreboard,
+                    inventory::effects::trigger_item_effects,
+                    wallet::drop_coins,
+                )
+                    .chain()
+                    .after(bullets::detect_collision_bullets),
+            )
+            .add_systems(
+                Update,
+                (
+                    interact::select_interaction_targets,
+                    interact::send_interactions,
+                )
+                    .chain()
+                    .before(inventory::pickup::update_pickup)
+                    .before(downed::revive_players),
+            )
+            .add_systems(Update, interact::spawn_interaction_prompts)
+            .add_systems(
+                PostUpdate,
+                interact::update_interaction_prompts.before(TransformSystem::TransformPropagate),
+            )
+            .add_systems(Update, (downed::revive_players, downed::update_revive_rings))
+            .add_systems(
+                Update,
+                (ability::use_abilities, ability::apply_ammo_pulses).chain(),
+            )
+            .add_systems(
+                Update,
+                (ability::update_barriers, ability::update_time_bubbles)
+                    .after(bullets::move_bullets),
+            )
+            .add_systems(
+                Update,
+                (modifiers::expire_timed_modifiers, modifiers::apply_stat_modifiers).chain(),
+            )
+            .add_systems(
+                Update,
+                (
+                    class::spawn_class_labels,
+                    // The starting gun must exist before `pickup_weapon` turns it into a held gun
+                    (class::choose_class, apply_deferred)
+                        .chain()
+                        .before(inventory::armory_manager::pickup_weapon),
+                    class::apply_passives,
+                ),
+            )
             .add_systems(PostUpdate, stats::player_death);
     }
 }
@@ -64,8 +154,15 @@ impl Plugin for PlayerPlugin {
 fn setup_players(
     mut commands: Commands,
     window: Query<Entity, With<PrimaryWindow>>,
-    assets: Res<PlayerAssets>,
+    classes: Res<CharacterClasses>,
+    controls: Res<controls::ControlsConfig>,
 ) {
-    setup::PlayerBundle::setup(&mut commands, &window, true, &assets);
-    setup::PlayerBundle::setup(&mut commands, &window, false, &assets);
+    setup::PlayerBundle::setup(
+        &mut commands,
+        &window,
+        None,
+        0,
+        &classes.0[0].assets,
+        &controls,
+    );
 }
@@ -0,0 +1,145 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+use strum::IntoEnumIterator;
+
+use super::{
+    damage::PlayerHealed,
+    inventory::item_manager::ItemId,
+    stats::{PlayerStats, Stat},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum ModifierKind {
+    Additive(f32),
+    Multiplicative(f32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModifierSource {
+    Item(ItemId),
+    Status(String),
+    Buff(String),
+    Synergy(String),
+}
+
+/// Modifiers applied together by a single source, removed together as well
+#[derive(Debug, Clone)]
+pub struct ModifierSet {
+    pub source: ModifierSource,
+    pub modifiers: Vec<(Stat, ModifierKind)>,
+}
+
+/// Values of the [`PlayerStats`] before any modifier is applied
+#[derive(Component, Clone)]
+pub struct BaseStats(pub PlayerStats);
+
+#[derive(Component, Default)]
+pub struct StatModifiers {
+    sets: Vec<ModifierSet>,
+}
+
+impl StatModifiers {
+    pub fn new() -> Self {
+        StatModifiers { sets: Vec::new() }
+    }
+
+    pub fn add(&mut self, source: ModifierSource, modifiers: Vec<(Stat, ModifierKind)>) {
+        if !modifiers.is_empty() {
+            self.sets.push(ModifierSet { source, modifiers });
+        }
+    }
+
+    /// Removes a single set added by `source`, returns false if there was none
+    pub fn remove(&mut self, source: &ModifierSource) -> bool {
+        if let Some(index) = self.sets.iter().position(|set| set.source == *source) {
+            self.sets.remove(index);
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn remove_all(&mut self, source: &ModifierSource) {
+        self.sets.retain(|set| set.source != *source);
+    }
+
+    pub fn has(&self, source: &ModifierSource) -> bool {
+        self.sets.iter().any(|set| set.source == *source)
+    }
+
+    pub fn compute(&self, stat: Stat, base: f32) -> f32 {
+        let mut added = 0.;
+        let mut multiplier = 1.;
+
+        for (modified, kind) in self.sets.iter().flat_map(|set| set.modifiers.iter()) {
+            if *modified == stat {
+                match kind {
+                    ModifierKind::Additive(value) => added += value,
+                    ModifierKind::Multiplicative(value) => multiplier *= value,
+                }
+            }
+        }
+        (base + added) * multiplier
+    }
+}
+
+/// Modifier sets removed from the `StatModifiers` once their timer finishes
+#[derive(Component, Default)]
+pub struct TimedModifiers(pub Vec<(ModifierSource, Timer)>);
+
+impl TimedModifiers {
+    /// Restarts the timer when `source` is already running
+    pub fn add(&mut self, source: ModifierSource, duration: f32) {
+        self.0.retain(|(running, _)| *running != source);
+        self.0
+            .push((source, Timer::from_seconds(duration, TimerMode::Once)));
+    }
+}
+
+pub fn expire_timed_modifiers(
+    time: Res<Time>,
+    mut players: Query<(&mut TimedModifiers, &mut StatModifiers)>,
+) {
+    for (mut timed, mut modifiers) in &mut players {
+        timed.0.retain_mut(|(source, timer)| {
+            if timer.tick(time.delta()).finished() {
+                modifiers.remove_all(source);
+                false
+            } else {
+                true
+            }
+        });
+    }
+}
+
+pub fn apply_stat_modifiers(
+    mut ev_healed: EventWriter<PlayerHealed>,
+    mut players: Query<
+        (Entity, &BaseStats, &StatModifiers, &mut PlayerStats),
+        Changed<StatModifiers>,
+    >,
+) {
+    for (entity, base, modifiers, mut stats) in &mut players {
+        let previous_max_health = stats.max_health;
+        let previous_health = stats.current_health;
+
+        for stat in Stat::iter() {
+            *stats.stat_mut(stat) = modifiers.compute(stat, base.0.stat(stat));
+        }
+        let health_gained = stats.max_health - previous_max_health;
+        // Losing max health never kills, downed players are left as is
+        if stats.current_health > 0. {
+            stats.current_health = (stats.current_health + health_gained)
+                .min(stats.max_health)
+                .max(1.);
+        }
+        if stats.current_health != previous_health || stats.max_health != previous_max_health {
+            ev_healed.send(PlayerHealed {
+                target: entity,
+                amount: stats.current_health - previous_health,
+            });
+        }
+        stats.current_stamina = stats.current_stamina.min(stats.max_stamina);
+        stats.current_shield = stats.current_shield.min(stats.max_shield);
+    }
+}
@@ -4,7 +4,10 @@ use bevy_rapier2d::prelude::*;
 use leafwing_input_manager::prelude::*;
 
 use crate::animation::{AnimationState, AnimationStateMachine};
+use crate::map::surface::SurfaceMap;
 
+use crate::player::class::ChoosingClass;
+use crate::player::downed::Downed;
 use crate::player::input::{PlayerActions, PlayerState};
 use crate::player::stats::PlayerStats;
 
@@ -25,22 +28,41 @@ impl RollStats {
             current_frame: 1u8,
         }
     }
+
+    pub fn is_invulnerable(&self, stats: &PlayerStats) -> bool {
+        let elapsed = self.since.elapsed();
+
+        elapsed >= stats.roll_invulnerability_start
+            && elapsed < stats.roll_invulnerability_start + stats.roll_invulnerability
+    }
 }
 
+#[derive(Component)]
+pub struct RollCooldown(pub Timer);
+
 pub fn start_roll(
     mut commands: Commands,
     mut query: Query<(
         Entity,
         &ActionState<PlayerActions>,
         &MoveDirection,
+        &mut PlayerStats,
         &mut AnimationState,
         &mut AnimationStateMachine,
-        Without<RollStats>,
+        (
+            Without<RollStats>,
+            Without<RollCooldown>,
+            Without<ChoosingClass>,
+            Without<Downed>,
+        ),
     )>,
 ) {
-    for (entity, action_state, direction, mut state, mut machine, _) in &mut query {
-        if action_state.pressed(PlayerActions::Roll) {
+    for (entity, action_state, direction, mut stats, mut state, mut machine, _) in &mut query {
+        if action_state.pressed(PlayerActions::Roll)
+            && stats.current_stamina >= stats.roll_stamina_cost
+        {
             // Why Just Pressed work half of the time
+            stats.current_stamina -= stats.roll_stamina_cost;
             let roll_stats;
             *state = if direction.value == Vec2::ZERO {
                 roll_stats = RollStats::new(MoveDirection { value: Vec2::NEG_Y });
@@ -71,18 +93,24 @@ pub fn start_roll(
 pub fn rolling(
     mut commands: Commands,
     time: Res<Time>,
+    surfaces: Res<SurfaceMap>,
     mut query: Query<(
         Entity,
+        &Transform,
         &mut Velocity,
         &mut RollStats,
         &mut AnimationStateMachine,
         &PlayerStats,
     )>,
 ) {
-    for (player, mut vel, mut roll_stats, mut machine, stats) in &mut query {
+    for (player, transform, mut vel, mut roll_stats, mut machine, stats) in &mut query {
         roll_stats.since.tick(time.delta());
 
-        vel.linvel = roll_stats.start_direction.value.clamp_length(0., 1.) * stats.roll_speed;
+        let surface = surfaces.at(transform.translation.truncate());
+        vel.linvel = roll_stats.start_direction.value.clamp_length(0., 1.)
+            * stats.roll_speed
+            * surface.speed()
+            + surface.push();
 
         if roll_stats.since.elapsed_secs()
             >= (stats.roll_duration.as_secs_f32() / 9.) * roll_stats.current_frame as f32
@@ -92,7 +120,32 @@ pub fn rolling(
         }
         if roll_stats.since.elapsed_secs() >= stats.roll_duration.as_secs_f32() {
             machine.set_manual(false);
-            commands.entity(player).remove::<RollStats>();
+            commands
+                .entity(player)
+                .remove::<RollStats>()
+                .insert(RollCooldown(Timer::new(stats.roll_cooldown, TimerMode::Once)));
+        }
+    }
+}
+
+pub fn roll_cooldown(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut RollCooldown)>,
+) {
+    for (player, mut cooldown) in &mut query {
+        if cooldown.0.tick(time.delta()).finished() {
+            commands.entity(player).remove::<RollCooldown>();
+        }
+    }
+}
+
+pub fn regenerate_stamina(time: Res<Time>, mut query: Query<&mut PlayerStats, Without<RollStats>>) {
+    for mut stats in &mut query {
+        if stats.current_stamina < stats.max_stamina {
+            stats.current_stamina = (stats.current_stamina
+                + stats.stamina_regen * time.delta_seconds())
+            .min(stats.max_stamina);
         }
     }
 }
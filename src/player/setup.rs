@@ -13,20 +13,34 @@ use input::PlayerActions;
 
 use super::{
     assets::PlayerAssets,
+    class::ChoosingClass,
+    controls::ControlsConfig,
+    damage::Resistances,
     direction::CursorPosition,
     direction::MoveDirection,
     input::{self, IsController, PlayerState},
-    inventory::{armory_manager::Armory, inventory_manager::Inventory},
+    interact::InteractionTarget,
+    inventory::{
+        armory_manager::Armory, inventory_manager::Inventory, synergy::ActiveSynergies,
+    },
+    join::PlayerIndex,
+    modifiers::{BaseStats, StatModifiers, TimedModifiers},
     stats::PlayerStats,
+    wallet::Wallet,
 };
 
 #[derive(Bundle)]
 pub struct PlayerBundle {
     pub name: Name,
+    pub index: PlayerIndex,
     pub state: AnimationState,
     pub state_machine: AnimationStateMachine,
     pub sprite: SpriteSheetBundle,
     pub stats: PlayerStats,
+    pub base_stats: BaseStats,
+    pub modifiers: StatModifiers,
+    pub timed_modifiers: TimedModifiers,
+    pub resistances: Resistances,
     pub action: InputManagerBundle<PlayerActions>,
     pub velocity: Velocity,
     pub zindex: AutoZindex,
@@ -34,6 +48,9 @@ pub struct PlayerBundle {
     pub cursor: CursorPosition,
     pub inventory: Inventory,
     pub armory: Armory,
+    pub synergies: ActiveSynergies,
+    pub wallet: Wallet,
+    pub interaction_target: InteractionTarget,
     pub active: ActiveEvents,
     pub rigid_body: RigidBody,
     pub gravity: GravityScale,
@@ -43,114 +60,143 @@ pub struct PlayerBundle {
 #[derive(Debug, Reflect, Component)]
 pub struct PlayerCollider;
 
+pub fn player_animations(assets: &PlayerAssets) -> AnimationStateMachine {
+    let mut machine = AnimationStateMachine::new_filled([
+        (
+            PlayerState::Idle,
+            assets.idle.clone(),
+            AnimationIndices { first: 0, last: 3 },
+            AnimationFlip::False,
+        ),
+        (
+            PlayerState::LeftFront,
+            assets.side_front.clone(),
+            AnimationIndices { first: 0, last: 5 },
+            AnimationFlip::XAxis,
+        ),
+        (
+            PlayerState::RightFront,
+            assets.side_front.clone(),
+            AnimationIndices { first: 0, last: 5 },
+            AnimationFlip::False,
+        ),
+        (
+            PlayerState::LeftBack,
+            assets.side_back.clone(),
+            AnimationIndices { first: 0, last: 5 },
+            AnimationFlip::XAxis,
+        ),
+        (
+            PlayerState::RightBack,
+            assets.side_back.clone(),
+            AnimationIndices { first: 0, last: 5 },
+            AnimationFlip::False,
+        ),
+        (
+            PlayerState::Front,
+            assets.front.clone(),
+            AnimationIndices { first: 0, last: 5 },
+            AnimationFlip::False,
+        ),
+        (
+            PlayerState::Back,
+            assets.back.clone(),
+            AnimationIndices { first: 0, last: 5 },
+            AnimationFlip::False,
+        ),
+        (
+            PlayerState::DodgeLeftFront,
+            assets.dodge_side_front.clone(),
+            AnimationIndices { first: 0, last: 8 },
+            AnimationFlip::XAxis,
+        ),
+        (
+            PlayerState::DodgeRightFront,
+            assets.dodge_side_front.clone(),
+            AnimationIndices { first: 0, last: 8 },
+            AnimationFlip::False,
+        ),
+        (
+            PlayerState::DodgeLeftBack,
+            assets.dodge_side_back.clone(),
+            AnimationIndices { first: 0, last: 8 },
+            AnimationFlip::XAxis,
+        ),
+        (
+            PlayerState::DodgeRightBack,
+            assets.dodge_side_back.clone(),
+            AnimationIndices { first: 0, last: 8 },
+            AnimationFlip::False,
+        ),
+        (
+            PlayerState::DodgeFront,
+            assets.dodge_front.clone(),
+            AnimationIndices { first: 0, last: 8 },
+            AnimationFlip::False,
+        ),
+        (
+            PlayerState::DodgeBack,
+            assets.dodge_back.clone(),
+            AnimationIndices { first: 0, last: 8 },
+            AnimationFlip::False,
+        ),
+    ]);
+
+    // Strafing loops the first half of the stride as a short shuffle step,
+    // backpedaling plays the whole stride in reverse
+    for (facing, sheet, flip) in [
+        (PlayerState::LeftFront, &assets.side_front, AnimationFlip::XAxis),
+        (PlayerState::RightFront, &assets.side_front, AnimationFlip::False),
+        (PlayerState::LeftBack, &assets.side_back, AnimationFlip::XAxis),
+        (PlayerState::RightBack, &assets.side_back, AnimationFlip::False),
+        (PlayerState::Front, &assets.front, AnimationFlip::False),
+        (PlayerState::Back, &assets.back, AnimationFlip::False),
+    ] {
+        machine.insert(
+            facing.strafe(),
+            (sheet.clone(), AnimationIndices { first: 0, last: 2 }, flip),
+        );
+        machine.insert(
+            facing.backpedal(),
+            (sheet.clone(), AnimationIndices { first: 5, last: 0 }, flip),
+        );
+    }
+    machine
+}
+
 impl PlayerBundle {
     pub fn setup(
         commands: &mut Commands,
         window: &Query<Entity, With<PrimaryWindow>>,
-        controller: bool,
-        assets: &Res<PlayerAssets>,
-    ) {
-        let state_machine = AnimationStateMachine::new_filled([
-            (
-                PlayerState::Idle,
-                assets.idle.clone(),
-                AnimationIndices { first: 0, last: 3 },
-                AnimationFlip::False,
-            ),
-            (
-                PlayerState::LeftFront,
-                assets.side_front.clone(),
-                AnimationIndices { first: 0, last: 5 },
-                AnimationFlip::XAxis,
-            ),
-            (
-                PlayerState::RightFront,
-                assets.side_front.clone(),
-                AnimationIndices { first: 0, last: 5 },
-                AnimationFlip::False,
-            ),
-            (
-                PlayerState::LeftBack,
-                assets.side_back.clone(),
-                AnimationIndices { first: 0, last: 5 },
-                AnimationFlip::XAxis,
-            ),
-            (
-                PlayerState::RightBack,
-                assets.side_back.clone(),
-                AnimationIndices { first: 0, last: 5 },
-                AnimationFlip::False,
-            ),
-            (
-                PlayerState::Front,
-                assets.front.clone(),
-                AnimationIndices { first: 0, last: 5 },
-                AnimationFlip::False,
-            ),
-            (
-                PlayerState::Back,
-                assets.back.clone(),
-                AnimationIndices { first: 0, last: 5 },
-                AnimationFlip::False,
-            ),
-            (
-                PlayerState::DodgeLeftFront,
-                assets.dodge_side_front.clone(),
-                AnimationIndices { first: 0, last: 8 },
-                AnimationFlip::XAxis,
-            ),
-            (
-                PlayerState::DodgeRightFront,
-                assets.dodge_side_front.clone(),
-                AnimationIndices { first: 0, last: 8 },
-                AnimationFlip::False,
-            ),
-            (
-                PlayerState::DodgeLeftBack,
-                assets.dodge_side_back.clone(),
-                AnimationIndices { first: 0, last: 8 },
-                AnimationFlip::XAxis,
-            ),
-            (
-                PlayerState::DodgeRightBack,
-                assets.dodge_side_back.clone(),
-                AnimationIndices { first: 0, last: 8 },
-                AnimationFlip::False,
-            ),
-            (
-                PlayerState::DodgeFront,
-                assets.dodge_front.clone(),
-                AnimationIndices { first: 0, last: 8 },
-                AnimationFlip::False,
-            ),
-            (
-                PlayerState::DodgeBack,
-                assets.dodge_back.clone(),
-                AnimationIndices { first: 0, last: 8 },
-                AnimationFlip::False,
-            ),
-        ]);
+        gamepad: Option<Gamepad>,
+        index: usize,
+        assets: &PlayerAssets,
+        controls: &ControlsConfig,
+    ) -> Entity {
+        let state_machine = player_animations(assets);
 
         let player = PlayerBundle {
-            name: bevy::core::Name::new("Player"),
+            name: bevy::core::Name::new(format!("Player {}", index + 1)),
+            index: PlayerIndex(index),
             state: AnimationState::new(&PlayerState::Idle),
             sprite: SpriteSheetBundle {
                 texture_atlas: assets.idle.clone(),
                 sprite: TextureAtlasSprite {
                     index: 0,
+                    color: assets.tint,
                     anchor: set_anchor(Vec2::new(17. / 2., 25. / 2. - 8.), Vec2::new(17., 25.)),
                     ..default()
                 },
-                transform: Transform::from_translation(Vec3::new(
-                    controller as i32 as f32 * 60.,
-                    0.,
-                    0.,
-                )),
+                transform: Transform::from_translation(Vec3::new(index as f32 * 60., 0., 0.)),
                 ..default()
             },
             state_machine,
             stats: PlayerStats::default(),
-            action: input::player_input_setup(controller),
+            base_stats: BaseStats(PlayerStats::default()),
+            modifiers: StatModifiers::new(),
+            timed_modifiers: TimedModifiers::default(),
+            resistances: Resistances::default(),
+            action: input::player_input_setup(gamepad, controls.profile(index)),
             zindex: AutoZindex,
             velocity: bevy_rapier2d::prelude::Velocity {
                 linvel: Vec2::new(0., 0.),
@@ -160,55 +206,45 @@ impl PlayerBundle {
             cursor: CursorPosition::default(),
             inventory: Inventory::new(),
             armory: Armory::new(),
+            synergies: ActiveSynergies::default(),
+            wallet: Wallet::default(),
+            interaction_target: InteractionTarget::default(),
             active: ActiveEvents::COLLISION_EVENTS,
             rigid_body: RigidBody::Dynamic,
             gravity: GravityScale(0.0),
             locked_axes: LockedAxes::ROTATION_LOCKED,
         };
-        if controller {
-            commands
-                .spawn(player)
-                .with_children(|parent| {
-                    parent.spawn((
-                        Collider::capsule_y(3.25, 13. / 2.),
-                        Sensor,
-                        TransformBundle::from(Transform::from_xyz(0., 6., 0.)),
-                        ColliderDebugColor(Color::BLUE),
-                        PlayerCollider,
-                    ));
-                    parent.spawn((
-                        Collider::capsule_y(0., 13. / 2.),
-                        TransformBundle::from(
-                            Transform::from_xyz(0., 0., 0.).with_scale(Vec3::new(1., 0.7, 1.)),
-                        ),
-                    ));
-                })
-                .insert(IsController);
+        let player_id = commands
+            .spawn(player)
+            .with_children(|parent| {
+                parent.spawn((
+                    Collider::capsule_y(3.25, 13. / 2.),
+                    Sensor,
+                    TransformBundle::from(Transform::from_xyz(0., 6., 0.)),
+                    ColliderDebugColor(Color::BLUE),
+                    PlayerCollider,
+                ));
+                parent.spawn((
+                    Collider::capsule_y(0., 13. / 2.),
+                    TransformBundle::from(
+                        Transform::from_xyz(0., 0., 0.).with_scale(Vec3::new(1., 0.7, 1.)),
+                    ),
+                ));
+            })
+            .insert(ChoosingClass::default())
+            .id();
+        if let Some(gamepad) = gamepad {
+            commands.entity(player_id).insert(IsController(gamepad));
         } else {
-            let player_id = commands
-                .spawn(player)
-                .with_children(|parent| {
-                    parent.spawn((
-                        Collider::capsule_y(3.25, 13. / 2.),
-                        Sensor,
-                        TransformBundle::from(Transform::from_xyz(0., 6., 0.)),
-                        ColliderDebugColor(Color::BLUE),
-                        PlayerCollider,
-                    ));
-                    parent.spawn((
-                        Collider::capsule_y(0., 13. / 2.),
-                        TransformBundle::from(
-                            Transform::from_xyz(0., 0., 0.).with_scale(Vec3::new(1., 0.7, 1.)),
-                        ),
-                    ));
-                })
-                .insert(InputManagerBundle::<Mouse>::default())
-                .id();
+            commands
+                .entity(player_id)
+                .insert(InputManagerBundle::<Mouse>::default());
 
             commands.entity(window.single()).insert(ActionStateDriver {
                 action: crate::mouse::Mouse::MousePosition,
                 targets: player_id.into(),
             });
         }
+        player_id
     }
 }
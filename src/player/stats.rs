@@ -1,36 +1,171 @@
 use std::time::Duration;
 
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use strum_macros::EnumIter;
 
-#[derive(Component, Reflect, Default)]
+use super::damage::PlayerKilled;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, EnumIter, Serialize, Deserialize)]
+pub enum Stat {
+    Speed,
+    Acceleration,
+    Deceleration,
+    MaxHealth,
+    DamagesMultiplier,
+    DamagesAdded,
+    RollSpeed,
+    RollStaminaCost,
+    MaxStamina,
+    StaminaRegen,
+    Armor,
+    MaxShield,
+    ShieldRegen,
+    MagnetRadius,
+}
+
+/// Highest fraction of the damages armor can absorb
+pub const MAX_ARMOR: f32 = 0.75;
+
+#[derive(Component, Reflect, Default, Clone)]
 pub struct PlayerStats {
+    /// Top speed reached while walking
     pub speed: f32,
+    pub acceleration: f32,
+    pub deceleration: f32,
     pub current_health: f32,
     pub max_health: f32,
     pub damages_multiplier: f32,
     pub damages_added: f32,
     pub roll_duration: Duration,
     pub roll_speed: f32,
+    pub roll_cooldown: Duration,
+    pub roll_invulnerability_start: Duration,
+    pub roll_invulnerability: Duration,
+    pub roll_stamina_cost: f32,
+    pub current_stamina: f32,
+    pub max_stamina: f32,
+    pub stamina_regen: f32,
+    /// Fraction of the damages absorbed, capped to `MAX_ARMOR`
+    pub armor: f32,
+    pub current_shield: f32,
+    pub max_shield: f32,
+    pub shield_regen: f32,
+    pub shield_regen_delay: Duration,
+    /// Distance from which auto-collect pickups are pulled in
+    pub magnet_radius: f32,
 }
 
+/// Shield does not regenerate until this timer finishes, restarted on every hit
+#[derive(Component)]
+pub struct ShieldCooldown(pub Timer);
+
 impl PlayerStats {
     pub fn default() -> Self {
         PlayerStats {
             speed: 50.,
+            acceleration: 500.,
+            deceleration: 700.,
             current_health: 100.,
             max_health: 100.,
             damages_multiplier: 1.,
             damages_added: 0.,
             roll_duration: Duration::from_secs_f32(0.5),
             roll_speed: 75.,
+            roll_cooldown: Duration::from_secs_f32(0.25),
+            roll_invulnerability_start: Duration::from_secs_f32(0.05),
+            roll_invulnerability: Duration::from_secs_f32(0.3),
+            roll_stamina_cost: 35.,
+            current_stamina: 100.,
+            max_stamina: 100.,
+            stamina_regen: 25.,
+            armor: 0.,
+            current_shield: 0.,
+            max_shield: 0.,
+            shield_regen: 15.,
+            shield_regen_delay: Duration::from_secs_f32(2.5),
+            magnet_radius: 40.,
+        }
+    }
+
+    pub fn stat(&self, stat: Stat) -> f32 {
+        match stat {
+            Stat::Speed => self.speed,
+            Stat::Acceleration => self.acceleration,
+            Stat::Deceleration => self.deceleration,
+            Stat::MaxHealth => self.max_health,
+            Stat::DamagesMultiplier => self.damages_multiplier,
+            Stat::DamagesAdded => self.damages_added,
+            Stat::RollSpeed => self.roll_speed,
+            Stat::RollStaminaCost => self.roll_stamina_cost,
+            Stat::MaxStamina => self.max_stamina,
+            Stat::StaminaRegen => self.stamina_regen,
+            Stat::Armor => self.armor,
+            Stat::MaxShield => self.max_shield,
+            Stat::ShieldRegen => self.shield_regen,
+            Stat::MagnetRadius => self.magnet_radius,
+        }
+    }
+
+    pub fn stat_mut(&mut self, stat: Stat) -> &mut f32 {
+        match stat {
+            Stat::Speed => &mut self.speed,
+            Stat::Acceleration => &mut self.acceleration,
+            Stat::Deceleration => &mut self.deceleration,
+            Stat::MaxHealth => &mut self.max_health,
+            Stat::DamagesMultiplier => &mut self.damages_multiplier,
+            Stat::DamagesAdded => &mut self.damages_added,
+            Stat::RollSpeed => &mut self.roll_speed,
+            Stat::RollStaminaCost => &mut self.roll_stamina_cost,
+            Stat::MaxStamina => &mut self.max_stamina,
+            Stat::StaminaRegen => &mut self.stamina_regen,
+            Stat::Armor => &mut self.armor,
+            Stat::MaxShield => &mut self.max_shield,
+            Stat::ShieldRegen => &mut self.shield_regen,
+            Stat::MagnetRadius => &mut self.magnet_radius,
+        }
+    }
+
+    /// Returns the health actually restored
+    pub fn heal(&mut self, amount: f32) -> f32 {
+        let previous = self.current_health;
+
+        self.current_health = (self.current_health + amount).min(self.max_health);
+        self.current_health - previous
+    }
+
+    /// Reduces damages by the armor, then drains the shield before the health
+    pub fn take_damage(&mut self, amount: f32) {
+        let amount = amount * (1. - self.armor.clamp(0., MAX_ARMOR));
+        let absorbed = amount.min(self.current_shield);
+
+        self.current_shield -= absorbed;
+        self.current_health -= amount - absorbed;
+    }
+}
+
+pub fn regenerate_shield(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut players: Query<(Entity, &mut PlayerStats, Option<&mut ShieldCooldown>)>,
+) {
+    for (entity, mut stats, cooldown) in &mut players {
+        if let Some(mut cooldown) = cooldown {
+            if cooldown.0.tick(time.delta()).finished() {
+                commands.entity(entity).remove::<ShieldCooldown>();
+            }
+        } else if stats.current_shield < stats.max_shield {
+            stats.current_shield =
+                (stats.current_shield + stats.shield_regen * time.delta_seconds())
+                    .min(stats.max_shield);
         }
     }
 }
 
-pub fn player_death(mut commands: Commands, players: Query<(Entity, &PlayerStats)>) {
-    for (entity, stats) in &players {
-        if stats.current_health <= 0. {
-            commands.entity(entity).despawn_recursive();
+pub fn player_death(mut commands: Commands, mut ev_killed: EventReader<PlayerKilled>) {
+    for killed in ev_killed.iter() {
+        if let Some(entity) = commands.get_entity(killed.target) {
+            entity.despawn_recursive();
         }
     }
 }
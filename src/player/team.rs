@@ -0,0 +1,116 @@
+use bevy::prelude::*;
+
+use super::join::PlayerIndex;
+
+#[derive(Component, Reflect, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Team(pub usize);
+
+impl Team {
+    pub fn color(&self) -> Color {
+        match self.0 % 4 {
+            0 => Color::rgb(0.89, 0.27, 0.27),
+            1 => Color::rgb(0.27, 0.52, 0.89),
+            2 => Color::rgb(0.35, 0.8, 0.35),
+            _ => Color::rgb(0.93, 0.8, 0.25),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TeamMode {
+    FreeForAll,
+    TwoVersusTwo,
+    Coop,
+}
+
+impl TeamMode {
+    pub fn team_of(&self, index: usize) -> Team {
+        match self {
+            TeamMode::FreeForAll => Team(index),
+            TeamMode::TwoVersusTwo => Team(index % 2),
+            TeamMode::Coop => Team(0),
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            TeamMode::FreeForAll => TeamMode::TwoVersusTwo,
+            TeamMode::TwoVersusTwo => TeamMode::Coop,
+            TeamMode::Coop => TeamMode::FreeForAll,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum FriendlyFire {
+    Off,
+    Reduced(f32),
+    Full,
+}
+
+impl FriendlyFire {
+    pub fn next(self) -> Self {
+        match self {
+            FriendlyFire::Off => FriendlyFire::Reduced(0.5),
+            FriendlyFire::Reduced(_) => FriendlyFire::Full,
+            FriendlyFire::Full => FriendlyFire::Off,
+        }
+    }
+}
+
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct TeamRules {
+    pub mode: TeamMode,
+    pub friendly_fire: FriendlyFire,
+}
+
+impl Default for TeamRules {
+    fn default() -> Self {
+        TeamRules {
+            mode: TeamMode::FreeForAll,
+            friendly_fire: FriendlyFire::Reduced(0.5),
+        }
+    }
+}
+
+impl TeamRules {
+    /// Multiplier applied to damages dealt by `attacker` to `target`,
+    /// bullets go through the target when it is `None`
+    pub fn damage_multiplier(&self, attacker: Option<&Team>, target: Option<&Team>) -> Option<f32> {
+        match (attacker, target) {
+            (Some(attacker), Some(target)) if attacker == target => match self.friendly_fire {
+                FriendlyFire::Off => None,
+                FriendlyFire::Reduced(multiplier) => Some(multiplier),
+                FriendlyFire::Full => Some(1.),
+            },
+            _ => Some(1.),
+        }
+    }
+}
+
+pub fn assign_teams(
+    mut commands: Commands,
+    rules: Res<TeamRules>,
+    mut players: Query<(Entity, &PlayerIndex, Option<&mut Team>)>,
+) {
+    for (entity, index, team) in &mut players {
+        match team {
+            Some(mut team) if rules.is_changed() => *team = rules.mode.team_of(index.0),
+            Some(_) => {}
+            None => {
+                commands.entity(entity).insert(rules.mode.team_of(index.0));
+            }
+        }
+    }
+}
+
+pub fn switch_team_rules(input: Res<Input<KeyCode>>, mut rules: ResMut<TeamRules>) {
+    if input.just_pressed(KeyCode::F3) {
+        rules.mode = rules.mode.next();
+        info!("Team mode: {:?}", rules.mode);
+    }
+    if input.just_pressed(KeyCode::F4) {
+        rules.friendly_fire = rules.friendly_fire.next();
+        info!("Friendly fire: {:?}", rules.friendly_fire);
+    }
+}
@@ -0,0 +1,67 @@
+use bevy::prelude::*;
+
+use crate::rendering::outline::Outline;
+
+use super::{
+    damage::PlayerKilled,
+    inventory::{
+        loot::{Loot, LootSpawner},
+        pickup::{MagneticPickupBundle, PickupBundle, PickupType},
+    },
+};
+
+/// Fraction of its coins a player drops when killed
+const DEATH_DROP: f32 = 0.5;
+
+#[derive(Component, Default)]
+pub struct Wallet {
+    pub coins: u32,
+}
+
+impl Wallet {
+    /// Returns false, spending nothing, when the player can't afford it
+    pub fn spend(&mut self, amount: u32) -> bool {
+        if self.coins < amount {
+            return false;
+        }
+        self.coins -= amount;
+        true
+    }
+}
+
+pub fn coin_pickup(
+    amount: u32,
+    pos: Vec2,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<Outline>>,
+    asset_server: &AssetServer,
+) -> MagneticPickupBundle {
+    PickupBundle::create(
+        meshes,
+        materials,
+        asset_server.load("items/Item__40.png"),
+        Vec2::new(16., 16.),
+        format!("{amount} coins"),
+        pos,
+        PickupType::Coins(amount),
+    )
+    .magnetic()
+}
+
+pub fn drop_coins(
+    mut ev_killed: EventReader<PlayerKilled>,
+    mut players: Query<&mut Wallet>,
+    mut spawner: LootSpawner,
+) {
+    for killed in ev_killed.iter() {
+        let Ok(mut wallet) = players.get_mut(killed.target) else {
+            continue;
+        };
+        let dropped = (wallet.coins as f32 * DEATH_DROP).ceil() as u32;
+
+        if dropped > 0 {
+            wallet.coins -= dropped;
+            spawner.spawn(&Loot::Coins(dropped), killed.position);
+        }
+    }
+}
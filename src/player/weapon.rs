@@ -8,7 +8,7 @@ use crate::{
 };
 
 use super::bullets::BulletType;
-use super::inventory::weapon_manager::GunAssets;
+use super::inventory::weapon_manager::{GunAssets, Guns};
 use super::{inventory::inventory_manager::Inventory, stats::PlayerStats};
 
 type ShootFn = fn(
@@ -38,6 +38,7 @@ pub type ReloadFn = fn(
 
 #[derive(Component)]
 pub struct GunStats {
+    pub gun: Option<Guns>,
     pub handle_position: Vec2,
     pub size: Vec2,
     pub barrel_length: f32,
@@ -54,6 +55,8 @@ pub struct GunStats {
     pub reload: ReloadFn,
     pub timer: Stopwatch,
     pub damage: f32,
+    /// Chance for each bullet to deal critical damages
+    pub crit_chance: f32,
     pub ammo: i32,
     pub max_ammo: i32,
     pub infinite: bool,
@@ -71,6 +74,7 @@ pub struct GunStats {
 impl Default for GunStats {
     fn default() -> Self {
         GunStats {
+            gun: None,
             handle_position: Vec2::new(2., 2.),
             size: Vec2::new(14., 9.),
             barrel_length: 12.,
@@ -80,6 +84,7 @@ impl Default for GunStats {
             shoot: auto_shoot_fn,
             reload: basic_reload_fn,
             damage: 10.,
+            crit_chance: 0.05,
             spread: (0_f32).to_radians(),
             speed: 90.,
             speed_spread: 0.,
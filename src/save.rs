@@ -0,0 +1,305 @@
+use std::{fs, path::PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    data::load_data,
+    player::{
+        damage::PlayerKilled,
+        downed::PlayerRevived,
+        inventory::{loot::Loot, weapon_manager::Guns, PickupItemEvent},
+        join::PlayerIndex,
+        wallet::drop_coins,
+    },
+};
+
+const SAVE_FILE: &str = "save.ron";
+/// Bumped whenever a change to `SaveData` can't be covered by `#[serde(default)]`,
+/// older versions are converted in `SaveData::migrate`
+pub const SAVE_VERSION: u32 = 1;
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SaveData::load())
+            .insert_resource(Unlocks(load_data("unlocks.ron")))
+            .add_systems(
+                Update,
+                (
+                    record_kills,
+                    record_pickups,
+                    record_revives,
+                    check_unlocks,
+                    write_save,
+                )
+                    .chain()
+                    // Killed players are despawned in `PostUpdate`, deaths must be read the same frame
+                    .after(drop_coins),
+            );
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct LifetimeStats {
+    pub kills: u32,
+    pub deaths: u32,
+    pub items_picked_up: u32,
+    pub revives: u32,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct PlayerProfile {
+    pub name: String,
+    pub stats: LifetimeStats,
+}
+
+/// Progression kept between runs, in the user data directory
+#[derive(Resource, Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct SaveData {
+    pub version: u32,
+    pub unlocked_guns: Vec<Guns>,
+    /// Names of items from `items.ron`
+    pub unlocked_items: Vec<String>,
+    /// Indexed by [`PlayerIndex`]
+    pub profiles: Vec<PlayerProfile>,
+    /// Set when the file on disk could neither be read nor backed up, it is then never overwritten
+    #[serde(skip)]
+    pub read_only: bool,
+}
+
+impl Default for SaveData {
+    fn default() -> Self {
+        SaveData {
+            version: SAVE_VERSION,
+            unlocked_guns: Vec::new(),
+            unlocked_items: Vec::new(),
+            profiles: Vec::new(),
+            read_only: false,
+        }
+    }
+}
+
+/// Only the version, read first to know how to parse the rest
+#[derive(Deserialize)]
+struct SaveHeader {
+    /// Missing from saves written before versioning, read as 0
+    #[serde(default)]
+    version: u32,
+}
+
+impl SaveData {
+    pub fn path() -> PathBuf {
+        dirs::data_dir()
+            .unwrap_or_default()
+            .join("HyperBlast")
+            .join(SAVE_FILE)
+    }
+
+    pub fn load() -> Self {
+        let path = Self::path();
+        let Ok(content) = fs::read_to_string(&path) else {
+            return SaveData::default();
+        };
+
+        Self::migrate(&content).unwrap_or_else(|err| {
+            // The broken or newer save is kept aside before a fresh one replaces it
+            let backup = path.with_extension("ron.bak");
+            let copied = fs::copy(&path, &backup);
+
+            warn!("Invalid save file {}: {err}", path.display());
+            match copied {
+                Ok(_) => {
+                    warn!(
+                        "Starting a new save, the old one is in {}",
+                        backup.display()
+                    );
+                    SaveData::default()
+                }
+                Err(err) => {
+                    warn!("Could not back up the save, progression won't be saved: {err}");
+                    SaveData {
+                        read_only: true,
+                        ..default()
+                    }
+                }
+            }
+        })
+    }
+
+    /// Parses a save written by any version of the game.
+    /// When `SAVE_VERSION` is bumped, the previous layout gets its own arm here,
+    /// parsed into a copy of the old struct and converted to the current one.
+    fn migrate(content: &str) -> Result<Self, String> {
+        let header: SaveHeader = ron::from_str(content).map_err(|err| err.to_string())?;
+
+        match header.version {
+            // Version 0 is the same layout without the version field,
+            // fields added since are filled with their defaults
+            0 | 1 => {
+                let mut save: SaveData = ron::from_str(content).map_err(|err| err.to_string())?;
+
+                save.version = SAVE_VERSION;
+                Ok(save)
+            }
+            version => Err(format!(
+                "unknown version {version}, this game reads up to {SAVE_VERSION}"
+            )),
+        }
+    }
+
+    pub fn save(&self) {
+        if self.read_only {
+            return;
+        }
+        let path = Self::path();
+        let content = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .expect("Save data should always be serializable");
+
+        if let Some(dir) = path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        if let Err(err) = fs::write(&path, content) {
+            warn!("Could not write the save to {}: {err}", path.display());
+        }
+    }
+
+    pub fn profile_mut(&mut self, index: usize) -> &mut PlayerProfile {
+        while self.profiles.len() <= index {
+            let name = format!("Player {}", self.profiles.len() + 1);
+            self.profiles.push(PlayerProfile { name, ..default() });
+        }
+        &mut self.profiles[index]
+    }
+
+    /// Stats summed over every profile
+    pub fn total_stats(&self) -> LifetimeStats {
+        let mut total = LifetimeStats::default();
+
+        for stats in self.profiles.iter().map(|profile| &profile.stats) {
+            total.kills += stats.kills;
+            total.deaths += stats.deaths;
+            total.items_picked_up += stats.items_picked_up;
+            total.revives += stats.revives;
+        }
+        total
+    }
+
+    pub fn is_unlocked(&self, loot: &Loot) -> bool {
+        match loot {
+            Loot::Gun(gun) => self.unlocked_guns.contains(gun),
+            Loot::Item(name) => self.unlocked_items.contains(name),
+            _ => true,
+        }
+    }
+
+    fn unlock(&mut self, loot: &Loot) {
+        match loot {
+            Loot::Gun(gun) => self.unlocked_guns.push(*gun),
+            Loot::Item(name) => self.unlocked_items.push(name.clone()),
+            _ => {}
+        }
+    }
+}
+
+/// Lifetime total, summed over every profile, needed for an unlock
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub enum Milestone {
+    Kills(u32),
+    ItemsPickedUp(u32),
+    Revives(u32),
+}
+
+impl Milestone {
+    fn is_reached(self, stats: &LifetimeStats) -> bool {
+        match self {
+            Milestone::Kills(count) => stats.kills >= count,
+            Milestone::ItemsPickedUp(count) => stats.items_picked_up >= count,
+            Milestone::Revives(count) => stats.revives >= count,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct Unlock {
+    pub milestone: Milestone,
+    /// Only guns and items can be locked
+    pub loot: Loot,
+}
+
+/// Loot missing from every roll until its milestone is reached
+#[derive(Resource)]
+pub struct Unlocks(pub Vec<Unlock>);
+
+impl Unlocks {
+    pub fn is_available(&self, loot: &Loot, save: &SaveData) -> bool {
+        save.is_unlocked(loot) || !self.0.iter().any(|unlock| unlock.loot == *loot)
+    }
+}
+
+fn record_kills(
+    mut save: ResMut<SaveData>,
+    mut ev_killed: EventReader<PlayerKilled>,
+    players: Query<&PlayerIndex>,
+) {
+    for killed in ev_killed.iter() {
+        if let Ok(index) = players.get(killed.target) {
+            save.profile_mut(index.0).stats.deaths += 1;
+        }
+        if let Some(Ok(index)) = killed
+            .killer
+            .filter(|killer| *killer != killed.target)
+            .map(|killer| players.get(killer))
+        {
+            save.profile_mut(index.0).stats.kills += 1;
+        }
+    }
+}
+
+fn record_pickups(
+    mut save: ResMut<SaveData>,
+    mut ev_pickup: EventReader<PickupItemEvent>,
+    players: Query<&PlayerIndex>,
+) {
+    for PickupItemEvent(_, player) in ev_pickup.iter() {
+        if let Ok(index) = players.get(*player) {
+            save.profile_mut(index.0).stats.items_picked_up += 1;
+        }
+    }
+}
+
+fn record_revives(
+    mut save: ResMut<SaveData>,
+    mut ev_revived: EventReader<PlayerRevived>,
+    players: Query<&PlayerIndex>,
+) {
+    for revived in ev_revived.iter() {
+        if let Ok(index) = players.get(revived.reviver) {
+            save.profile_mut(index.0).stats.revives += 1;
+        }
+    }
+}
+
+fn check_unlocks(mut save: ResMut<SaveData>, unlocks: Res<Unlocks>) {
+    if !save.is_changed() {
+        return;
+    }
+    let total = save.total_stats();
+
+    for unlock in &unlocks.0 {
+        if !save.is_unlocked(&unlock.loot) && unlock.milestone.is_reached(&total) {
+            info!("Unlocked {:?}", unlock.loot);
+            save.unlock(&unlock.loot);
+        }
+    }
+}
+
+fn write_save(save: Res<SaveData>) {
+    if save.is_changed() && !save.is_added() {
+        save.save();
+    }
+}
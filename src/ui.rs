@@ -2,7 +2,17 @@ use bevy::prelude::*;
 use bevy_asset_loader::prelude::*;
 
 use crate::player::{
-    stats::PlayerStats,
+    ability::{Abilities, AbilitySlot},
+    inventory::{
+        inventory_manager::Inventory,
+        item_manager::Items,
+        synergy::{Synergies, SynergyActivated},
+    },
+    damage::{PlayerDamaged, PlayerHealed, PlayerKilled, Scoreboard},
+    join::PlayerIndex,
+    stats::{PlayerStats, MAX_ARMOR},
+    team::Team,
+    wallet::Wallet,
     weapon::{GunEntity, GunStats},
 };
 
@@ -13,13 +23,37 @@ impl Plugin for UiPlugin {
         app.init_collection::<UiAssets>()
             .add_systems(Startup, setup_ui)
             .add_systems(Update, spawn_player_ui)
-            .add_systems(PostUpdate, (manage_health_bars, manage_ammo_count));
+            .add_systems(Update, flash_damaged_health_bars)
+            .add_systems(Update, (notify_synergies, update_notifications))
+            .add_systems(
+                PostUpdate,
+                (
+                    manage_health_bars,
+                    manage_defense_bars,
+                    manage_ammo_count,
+                    manage_ability_indicators,
+                    manage_inventory_indicators,
+                    manage_team_colors,
+                    manage_kill_counters,
+                    manage_coin_counters,
+                ),
+            );
     }
 }
 
 #[derive(Component)]
 pub struct UiRoot;
 
+const NOTIFICATION_DURATION: f32 = 2.5;
+const NOTIFICATION_RISE: f32 = 12.;
+
+/// Text floating up from a player before fading out
+#[derive(Component)]
+pub struct Notification {
+    pub timer: Timer,
+    pub origin: Vec2,
+}
+
 #[derive(Component)]
 pub struct HealthBarFg;
 
@@ -29,11 +63,58 @@ pub struct HealthBar {
     pub health_bar_fg: Handle<TextureAtlas>,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum DefenseKind {
+    Shield,
+    Armor,
+}
+
+impl DefenseKind {
+    fn color(&self) -> Color {
+        match self {
+            DefenseKind::Shield => Color::rgb(0.3, 0.8, 1.),
+            DefenseKind::Armor => Color::rgb(0.7, 0.7, 0.75),
+        }
+    }
+}
+
+/// Thin bar shown under the health bar, hidden while the player has none of it
+#[derive(Component)]
+pub struct DefenseBar {
+    pub player_id: Entity,
+    pub kind: DefenseKind,
+}
+
+#[derive(Component)]
+pub struct DefenseBarFill;
+
+/// Tints the health bar for a short time after the player got hit
+#[derive(Component)]
+pub struct DamageFlash {
+    pub timer: Timer,
+    pub crit: bool,
+}
+
 #[derive(Component)]
 pub struct AmmoCounter {
     pub player_id: Entity,
 }
 
+#[derive(Component)]
+pub struct InventoryIndicator {
+    pub player_id: Entity,
+}
+
+#[derive(Component)]
+pub struct AbilityIndicator {
+    pub player_id: Entity,
+}
+
+#[derive(Component)]
+pub struct PlayerLabel {
+    pub player_id: Entity,
+}
+
 #[derive(AssetCollection, Resource)]
 pub struct UiAssets {
     #[asset(path = "ui/healthbar_bg.png")]
@@ -52,9 +133,41 @@ fn spawn_player_ui(
     ui_assets: Res<UiAssets>,
     asset_server: Res<AssetServer>,
     ui_root: Query<(Entity, With<UiRoot>)>,
-    players_query: Query<(Entity, (Without<PlayerUiAccess>, With<PlayerStats>))>,
+    players_query: Query<(Entity, &PlayerIndex, (Without<PlayerUiAccess>, With<PlayerStats>))>,
 ) {
-    for (id, _) in &players_query {
+    for (id, index, _) in &players_query {
+        // Create player label:
+        let label_id = commands
+            .spawn((
+                TextBundle::from_sections([
+                    TextSection::new(
+                        format!("P{}", index.0 + 1),
+                        TextStyle {
+                            font: asset_server.load("fonts/Extended_font.ttf"),
+                            font_size: 20.0,
+                            color: Color::WHITE,
+                        },
+                    ),
+                    TextSection::new(
+                        "",
+                        TextStyle {
+                            font: asset_server.load("fonts/Extended_font.ttf"),
+                            font_size: 15.0,
+                            color: Color::WHITE,
+                        },
+                    ),
+                    TextSection::new(
+                        "",
+                        TextStyle {
+                            font: asset_server.load("fonts/Extended_font.ttf"),
+                            font_size: 15.0,
+                            color: Color::GOLD,
+                        },
+                    ),
+                ]),
+                PlayerLabel { player_id: id },
+            ))
+            .id();
         // Create healthbar:
         let fg_handle = asset_server.load("ui/healthbar_fg.png");
         let fg_atlas = TextureAtlas::from_grid(fg_handle, Vec2::new(100.0, 10.0), 1, 1, None, None);
@@ -93,6 +206,43 @@ fn spawn_player_ui(
                 ));
             })
             .id();
+        // Create shield and armor bars:
+        let defense_ids = [DefenseKind::Shield, DefenseKind::Armor].map(|kind| {
+            commands
+                .spawn((
+                    DefenseBar { player_id: id, kind },
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Px(100. * 3.),
+                            height: Val::Px(3. * 3.),
+                            margin: UiRect::new(
+                                Val::Px(8. * 3.),
+                                Val::Px(0.),
+                                Val::Px(1. * 3.),
+                                Val::Px(0.),
+                            ),
+                            ..default()
+                        },
+                        background_color: Color::rgba(0., 0., 0., 0.5).into(),
+                        ..default()
+                    },
+                ))
+                .with_children(|bar| {
+                    bar.spawn((
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Percent(0.),
+                                height: Val::Percent(100.),
+                                ..default()
+                            },
+                            background_color: kind.color().into(),
+                            ..default()
+                        },
+                        DefenseBarFill,
+                    ));
+                })
+                .id()
+        });
         // Create Ammo count:
         let count_id = commands
             .spawn((
@@ -121,6 +271,34 @@ fn spawn_player_ui(
                 AmmoCounter { player_id: id },
             ))
             .id();
+        // Create ability cooldown:
+        let ability_id = commands
+            .spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("fonts/Extended_font.ttf"),
+                        font_size: 15.0,
+                        color: Color::WHITE,
+                    },
+                ),
+                AbilityIndicator { player_id: id },
+            ))
+            .id();
+        // Create inventory content:
+        let inventory_id = commands
+            .spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("fonts/Extended_font.ttf"),
+                        font_size: 12.0,
+                        color: Color::WHITE,
+                    },
+                ),
+                InventoryIndicator { player_id: id },
+            ))
+            .id();
         let player_ui_id = commands
             .spawn(NodeBundle {
                 style: Style {
@@ -133,8 +311,12 @@ fn spawn_player_ui(
                 ..default()
             })
             .insert(Name::new("PlayerUI"))
+            .add_child(label_id)
             .add_child(hb_id)
+            .push_children(&defense_ids)
             .add_child(count_id)
+            .add_child(ability_id)
+            .add_child(inventory_id)
             .id();
         commands.entity(ui_root.single().0).add_child(player_ui_id);
         commands.entity(id).insert(PlayerUiAccess {
@@ -144,26 +326,71 @@ fn spawn_player_ui(
     }
 }
 
+/// Redraws a health bar when its player is damaged or healed, and removes the UI of players
+/// who died or left
 fn manage_health_bars(
     mut commands: Commands,
     mut atlases: ResMut<Assets<TextureAtlas>>,
-    players_query: Query<(&PlayerStats, &PlayerUiAccess)>,
-    health_bars_query: Query<(&Children, Entity, &HealthBar)>,
+    mut ev_damaged: EventReader<PlayerDamaged>,
+    mut ev_healed: EventReader<PlayerHealed>,
+    mut removed_players: RemovedComponents<PlayerStats>,
+    players_query: Query<&PlayerStats>,
+    health_bars_query: Query<(&Children, &Parent, Ref<HealthBar>)>,
     mut health_bars_fg_query: Query<(&mut Style, With<HealthBarFg>)>,
 ) {
-    for (children, id, healthbar_struct) in &health_bars_query {
+    let changed: Vec<Entity> = ev_damaged
+        .iter()
+        .map(|damaged| damaged.target)
+        .chain(ev_healed.iter().map(|healed| healed.target))
+        .collect();
+    let removed: Vec<Entity> = removed_players.iter().collect();
+
+    for (children, player_ui, healthbar_struct) in &health_bars_query {
+        if removed.contains(&healthbar_struct.player_id) {
+            commands.entity(player_ui.get()).despawn_recursive();
+            continue;
+        }
+        if !healthbar_struct.is_added() && !changed.contains(&healthbar_struct.player_id) {
+            continue;
+        }
+        let Ok(stats) = players_query.get(healthbar_struct.player_id) else {
+            continue;
+        };
         for childrens_entity in children {
             if let Ok((mut fg_style, _)) = health_bars_fg_query.get_mut(*childrens_entity) {
-                match players_query.get(healthbar_struct.player_id) {
-                    Ok((stats, _)) => {
-                        let health_bar = atlases.get_mut(&healthbar_struct.health_bar_fg);
-                        let percentage = stats.current_health / stats.max_health * 100.;
-
-                        health_bar.unwrap().textures[0] =
-                            Rect::new(100. - percentage, 0., 100., 10.);
-                        fg_style.width = Val::Px(percentage * 3.);
-                    }
-                    Err(_) => commands.get_entity(id).unwrap().despawn_recursive(),
+                let health_bar = atlases.get_mut(&healthbar_struct.health_bar_fg);
+                let percentage = (stats.current_health / stats.max_health * 100.).clamp(0., 100.);
+
+                health_bar.unwrap().textures[0] = Rect::new(100. - percentage, 0., 100., 10.);
+                fg_style.width = Val::Px(percentage * 3.);
+            }
+        }
+    }
+}
+
+fn manage_defense_bars(
+    players_query: Query<&PlayerStats>,
+    mut bars_query: Query<(&Children, &mut Style, &DefenseBar)>,
+    mut fills_query: Query<&mut Style, (With<DefenseBarFill>, Without<DefenseBar>)>,
+) {
+    for (children, mut style, bar) in &mut bars_query {
+        if let Ok(stats) = players_query.get(bar.player_id) {
+            let ratio = match bar.kind {
+                DefenseKind::Shield if stats.max_shield > 0. => {
+                    Some(stats.current_shield / stats.max_shield)
+                }
+                DefenseKind::Armor if stats.armor > 0. => Some(stats.armor.min(MAX_ARMOR) / MAX_ARMOR),
+                _ => None,
+            };
+
+            style.display = if ratio.is_some() {
+                Display::Flex
+            } else {
+                Display::None
+            };
+            for child in children {
+                if let Ok(mut fill_style) = fills_query.get_mut(*child) {
+                    fill_style.width = Val::Percent(ratio.unwrap_or(0.) * 100.);
                 }
             }
         }
@@ -208,6 +435,219 @@ fn manage_ammo_count(
     }
 }
 
+fn flash_damaged_health_bars(
+    mut commands: Commands,
+    mut ev_damaged: EventReader<PlayerDamaged>,
+    players: Query<&PlayerUiAccess>,
+) {
+    for damaged in ev_damaged.iter() {
+        if let Ok(access) = players.get(damaged.target) {
+            commands.entity(access.health_bar_id).insert(DamageFlash {
+                timer: Timer::from_seconds(0.15, TimerMode::Once),
+                crit: damaged.crit,
+            });
+        }
+    }
+}
+
+fn notify_synergies(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    synergies: Res<Synergies>,
+    mut ev_activated: EventReader<SynergyActivated>,
+    players: Query<(&Transform, Option<&Team>)>,
+) {
+    for activated in ev_activated.iter() {
+        let Ok((transform, team)) = players.get(activated.player) else {
+            continue;
+        };
+        let origin = transform.translation.truncate() + Vec2::new(0., 20.);
+
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(
+                    format!(
+                        "Synergy: {}",
+                        synergies.0[activated.synergy].definition.name
+                    ),
+                    TextStyle {
+                        font: asset_server.load("fonts/Extended_font.ttf"),
+                        font_size: 20.0,
+                        color: team.map_or(Color::WHITE, Team::color),
+                    },
+                ),
+                transform: Transform::from_translation(origin.extend(100.))
+                    .with_scale(Vec3::splat(0.25)),
+                ..default()
+            },
+            Notification {
+                timer: Timer::from_seconds(NOTIFICATION_DURATION, TimerMode::Once),
+                origin,
+            },
+        ));
+    }
+}
+
+fn update_notifications(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut notifications: Query<(Entity, &mut Transform, &mut Text, &mut Notification)>,
+) {
+    for (entity, mut transform, mut text, mut notification) in &mut notifications {
+        if notification.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let progress = notification.timer.percent();
+
+        transform.translation.y = notification.origin.y + progress * NOTIFICATION_RISE;
+        for section in &mut text.sections {
+            section.style.color.set_a(1. - progress * progress);
+        }
+    }
+}
+
+fn manage_kill_counters(
+    scoreboard: Res<Scoreboard>,
+    mut ev_killed: EventReader<PlayerKilled>,
+    players: Query<&PlayerIndex>,
+    mut labels: Query<(&mut Text, &PlayerLabel)>,
+) {
+    if ev_killed.is_empty() {
+        return;
+    }
+    ev_killed.clear();
+    for (mut text, label) in &mut labels {
+        if let Some(score) = players
+            .get(label.player_id)
+            .ok()
+            .and_then(|index| scoreboard.0.get(&index.0))
+        {
+            text.sections[1].value = format!(" {} kills", score.kills);
+        }
+    }
+}
+
+fn manage_coin_counters(
+    wallets: Query<&Wallet, Changed<Wallet>>,
+    mut labels: Query<(&mut Text, &PlayerLabel)>,
+) {
+    for (mut text, label) in &mut labels {
+        if let Ok(wallet) = wallets.get(label.player_id) {
+            text.sections[2].value = format!(" {}c", wallet.coins);
+        }
+    }
+}
+
+fn manage_inventory_indicators(
+    items: Res<Items>,
+    players: Query<&Inventory>,
+    mut texts: Query<(&mut Text, &InventoryIndicator)>,
+) {
+    for (mut text, indicator) in &mut texts {
+        let Ok(inventory) = players.get(indicator.player_id) else {
+            text.sections[0].value.clear();
+            continue;
+        };
+        let slots: Vec<String> = inventory
+            .slots
+            .iter()
+            .enumerate()
+            .map(|(index, stack)| {
+                let name = &items.get(stack.item).definition.name;
+                let name = if stack.count > 1 {
+                    format!("{name} x{}", stack.count)
+                } else {
+                    name.clone()
+                };
+
+                if index == inventory.selected {
+                    format!("[{name}]")
+                } else {
+                    name
+                }
+            })
+            .collect();
+
+        let quick = inventory.quick_slot.map_or(String::new(), |stack| {
+            let name = &items.get(stack.item).definition.name;
+            let cooldown = inventory
+                .cooldowns
+                .get(&stack.item)
+                .filter(|cooldown| !cooldown.finished())
+                .map_or(String::new(), |cooldown| {
+                    format!(" {:.1}s", cooldown.remaining_secs())
+                });
+
+            format!("{name} x{}{cooldown} | ", stack.count)
+        });
+
+        text.sections[0].value = format!(
+            "{quick}{} ({}/{})",
+            slots.join(" "),
+            inventory.slots.len(),
+            inventory.capacity
+        );
+    }
+}
+
+fn manage_ability_indicators(
+    abilities: Res<Abilities>,
+    players: Query<&AbilitySlot>,
+    mut texts: Query<(&mut Text, &AbilityIndicator)>,
+) {
+    for (mut text, indicator) in &mut texts {
+        let section = &mut text.sections[0];
+
+        match players.get(indicator.player_id) {
+            Ok(slot) if slot.cooldown.finished() => {
+                section.value = abilities.0[slot.ability].definition.name.clone();
+                section.style.color = Color::WHITE;
+            }
+            Ok(slot) => {
+                let remaining = slot.cooldown.remaining_secs();
+
+                section.value = format!(
+                    "{} {:.1}s",
+                    abilities.0[slot.ability].definition.name, remaining
+                );
+                section.style.color = Color::GRAY;
+            }
+            Err(_) => section.value.clear(),
+        }
+    }
+}
+
+fn manage_team_colors(
+    mut commands: Commands,
+    time: Res<Time>,
+    teams: Query<&Team>,
+    mut labels: Query<(&mut Text, &PlayerLabel)>,
+    mut health_bars: Query<(Entity, &mut BackgroundColor, &HealthBar, Option<&mut DamageFlash>)>,
+) {
+    for (mut text, label) in &mut labels {
+        if let Ok(team) = teams.get(label.player_id) {
+            text.sections[0].style.color = team.color();
+        }
+    }
+    for (entity, mut background, health_bar, flash) in &mut health_bars {
+        if let Some(mut flash) = flash {
+            if !flash.timer.tick(time.delta()).finished() {
+                *background = if flash.crit {
+                    Color::YELLOW.into()
+                } else {
+                    Color::WHITE.into()
+                };
+                continue;
+            }
+            commands.entity(entity).remove::<DamageFlash>();
+        }
+        if let Ok(team) = teams.get(health_bar.player_id) {
+            *background = team.color().into();
+        }
+    }
+}
+
 pub fn setup_ui(mut commands: Commands) {
     commands
         .spawn(NodeBundle {